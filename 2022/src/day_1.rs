@@ -1,86 +1,102 @@
-use std::{str::Utf8Error, num::ParseIntError};
-
-use crate::Input;
+use std::{io::BufRead, num::ParseIntError, collections::BinaryHeap, cmp::Reverse};
 
 #[derive(Debug)]
-pub(crate) enum CalorieListParseError {
-	Utf8Error(Utf8Error),
+pub(crate) enum CalorieListError {
+	Io(std::io::Error),
 	InvalidCalorie(ParseIntError),
-	EmptyList,
+	// an elf's total doesn't fit in a `usize`
+	Overflow,
+	NotEnoughElves {
+		wanted: usize,
+		found: usize,
+	},
 }
 
-impl From<Utf8Error> for CalorieListParseError {
-    fn from(err: Utf8Error) -> Self {
-        Self::Utf8Error(err)
+impl From<std::io::Error> for CalorieListError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
     }
 }
 
-#[derive(Debug)]
-struct Elf {
-	calories: Vec<usize>,
+pub(crate) struct ElfTotals<R> {
+	lines: std::io::Lines<R>,
 }
 
-impl Elf {
-	fn total(&self) -> usize {
-		self.calories.iter().sum()
+impl<R: BufRead> ElfTotals<R> {
+	pub fn new(reader: R) -> Self {
+		Self { lines: reader.lines() }
 	}
 }
 
-impl TryFrom<&str> for Elf {
-	type Error = CalorieListParseError;
+impl<R: BufRead> Iterator for ElfTotals<R> {
+	type Item = Result<usize, CalorieListError>;
 
-	fn try_from(input: &str) -> Result<Self, Self::Error> {
-		input.split('\n')
-			.map(|cal| cal.trim().parse::<usize>())
-			.collect::<Result<_, _>>()
-			.map(|calories| Self { calories })
-			.map_err(CalorieListParseError::InvalidCalorie)
-	}
-}
+	fn next(&mut self) -> Option<Self::Item> {
+		let mut total: Option<usize> = None;
 
-#[derive(Debug)]
-pub(crate) struct CalorieList {
-	elves: Vec<Elf>,
-}
+		for line in self.lines.by_ref() {
+			let line = match line {
+				Ok(line) => line,
+				Err(err) => return Some(Err(err.into())),
+			};
+
+			let line = line.trim();
 
-impl Input for CalorieList {
-    type Error = CalorieListParseError;
-
-    fn parse_str(data: &str) -> Result<Self, Self::Error> {
-		data.trim()
-			.split("\n\n")
-			.map(Elf::try_from)
-			.collect::<Result<_, _>>()
-			.map(|elves| Self { elves })
-			.and_then(|list| {
-				if list.elves.is_empty() {
-					Err(CalorieListParseError::EmptyList)
-				} else {
-					Ok(list)
+			// elves are separated by blank lines, and any extra ones don't start a new elf
+			if line.is_empty() {
+				if total.is_some() {
+					break;
 				}
-			})
-    }
+				continue;
+			}
+
+			match line.parse::<usize>() {
+				Ok(calories) => match total.unwrap_or(0).checked_add(calories) {
+					Some(sum) => total = Some(sum),
+					None => return Some(Err(CalorieListError::Overflow)),
+				},
+				Err(err) => return Some(Err(CalorieListError::InvalidCalorie(err))),
+			}
+		}
+
+		total.map(Ok)
+	}
 }
 
-impl CalorieList {
-	pub fn top(&self) -> usize {
-        self.elves
-			.iter()
-			.map(Elf::total)
-			.max()
-			.unwrap()
-    }
+// largest first
+pub(crate) fn top_n<R: BufRead>(reader: R, n: usize) -> Result<Vec<usize>, CalorieListError> {
+	let mut heap = BinaryHeap::with_capacity(n + 1);
+	let mut found = 0;
 
-	pub fn top_n(&self, n: usize) -> usize {
-        let mut sums = self.elves
-			.iter()
-			.map(Elf::total)
-			.collect::<Vec<usize>>();
+	for total in ElfTotals::new(reader) {
+		heap.push(Reverse(total?));
+		found += 1;
 
-		sums.sort_unstable_by(|a, b| b.cmp(a));
+		if heap.len() > n {
+			heap.pop();
+		}
+	}
 
-		assert!(sums.len() >= n);
-		
-		sums.iter().take(n).sum()
-    }
+	if found < n {
+		return Err(CalorieListError::NotEnoughElves { wanted: n, found });
+	}
+
+	Ok(heap.into_sorted_vec()
+		.into_iter()
+		.map(|Reverse(total)| total)
+		.collect())
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn overflow() {
+		let list = format!("1\n\n{}\n1\n", usize::MAX);
+		let mut totals = ElfTotals::new(list.as_bytes());
+
+		assert!(matches!(totals.next(), Some(Ok(1))));
+		assert!(matches!(totals.next(), Some(Err(CalorieListError::Overflow))));
+	}
+}
\ No newline at end of file
@@ -1,87 +1,189 @@
 use crate::Part;
 
-pub(crate) fn solve(data: &[u8], part: Part) {
-	enum Instruction {
-		NoOp,
-		AddX(isize),
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Instruction {
+	NoOp,
+	AddX(isize),
+}
+
+impl Instruction {
+	fn execution_time(&self) -> usize {
+		match self {
+			Self::NoOp => 1,
+			Self::AddX(_) => 2,
+		}
 	}
+}
+
+impl TryFrom<&str> for Instruction {
+	type Error = ();
+
+	fn try_from(string: &str) -> Result<Self, Self::Error> {
+		if string == "noop" {
+			Ok(Instruction::NoOp)
+		} else {
+			let (i, value) = string.split_once(' ').ok_or(())?;
+			let value = value.parse::<isize>().map_err(|_| ())?;
 
-	impl Instruction {
-		fn execution_time(&self) -> usize {
-			match self {
-				Self::NoOp => 1,
-				Self::AddX(_) => 2,
+			if i == "addx" {
+				Ok(Instruction::AddX(value))
+			} else {
+				Err(())
 			}
 		}
 	}
+}
 
-	impl TryFrom<&str> for Instruction {
-		type Error = ();
+pub(crate) trait Observer {
+	// cycles are numbered from 1, and `x` is the register's value during the cycle
+	fn observe(&mut self, cycle: usize, x: isize);
+}
 
-		fn try_from(string: &str) -> Result<Self, Self::Error> {
-			if string == "noop" {
-				Ok(Instruction::NoOp)
-			} else {
-				let (i, value) = string.split_once(' ').ok_or(())?;
-				let value = value.parse::<isize>().map_err(|_| ())?;
+#[derive(Debug)]
+pub(crate) struct Cpu {
+	x: isize,
+	cycle: usize,
+}
+
+impl Cpu {
+	pub fn new() -> Self {
+		Self { x: 1, cycle: 0 }
+	}
+
+	pub fn execute(&mut self, program: &[Instruction], observers: &mut [&mut dyn Observer]) {
+		for instruction in program {
+			for _ in 0..instruction.execution_time() {
+				self.cycle += 1;
 
-				if i == "addx" {
-					Ok(Instruction::AddX(value))
-				} else {
-					Err(())
+				for observer in observers.iter_mut() {
+					observer.observe(self.cycle, self.x);
 				}
 			}
+
+			match instruction {
+				Instruction::NoOp => (),
+				Instruction::AddX(value) => self.x += value,
+			}
 		}
 	}
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct SignalStrength {
+	total: isize,
+}
+
+impl Observer for SignalStrength {
+	fn observe(&mut self, cycle: usize, x: isize) {
+		const FIRST_CYCLE: usize = 20;
+		const CYCLE_INTERVAL: usize = 40;
+
+		if cycle >= FIRST_CYCLE && (cycle - FIRST_CYCLE).is_multiple_of(CYCLE_INTERVAL) {
+			self.total += cycle as isize * x;
+		}
+	}
+}
+
+pub(crate) const CRT_WIDTH: usize = 40;
+pub(crate) const CRT_HEIGHT: usize = 6;
+
+pub(crate) type Framebuffer = [[bool; CRT_WIDTH]; CRT_HEIGHT];
 
-	let instructions = std::str::from_utf8(&data).unwrap()
+// each letter is 4 pixels wide, with a 1 pixel gap before the next
+const GLYPHS: [(char, [&str; CRT_HEIGHT]); 17] = [
+	('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+	('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+	('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+	('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+	('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+	('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+	('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+	('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+	('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+	('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+	('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+	('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+	('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+	('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+	('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+	('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+	('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+const GLYPH_WIDTH: usize = 4;
+const GLYPH_SPACING: usize = 5;
+
+#[derive(Debug)]
+pub(crate) enum OcrError {
+	UnknownGlyph(usize),
+}
+
+#[derive(Debug)]
+pub(crate) struct Crt {
+	framebuffer: Framebuffer,
+}
+
+impl Crt {
+	pub fn new() -> Self {
+		Self { framebuffer: [[false; CRT_WIDTH]; CRT_HEIGHT] }
+	}
+
+	pub fn framebuffer(&self) -> Framebuffer {
+		self.framebuffer
+	}
+
+	pub fn read(&self) -> Result<String, OcrError> {
+		(0..CRT_WIDTH).step_by(GLYPH_SPACING)
+			.map(|column| {
+				GLYPHS.iter()
+					.find(|(_, glyph)| {
+						glyph.iter().zip(self.framebuffer.iter()).all(|(glyph_row, row)| {
+							glyph_row.bytes()
+								.zip(&row[column..column + GLYPH_WIDTH])
+								.all(|(g, lit)| (g == b'#') == *lit)
+						})
+					})
+					.map(|(letter, _)| *letter)
+					.ok_or(OcrError::UnknownGlyph(column))
+			})
+			.collect()
+	}
+}
+
+impl Observer for Crt {
+	fn observe(&mut self, cycle: usize, x: isize) {
+		let position = (cycle - 1) % CRT_WIDTH;
+		let row = (cycle - 1) / CRT_WIDTH;
+
+		if let Some(row) = self.framebuffer.get_mut(row) {
+			row[position] = isize::abs_diff(x, position as isize) < 2;
+		}
+	}
+}
+
+pub(crate) fn solve(data: &[u8], part: Part) {
+	let instructions = std::str::from_utf8(data).unwrap()
 		.split('\n')
 		.map(Instruction::try_from)
 		.collect::<Result<Vec<_>, _>>()
 		.unwrap();
 
+	let mut strength = SignalStrength::default();
+	let mut crt = Crt::new();
 
-	let mut x_reg = 1;
-	let mut strength = 0_isize;
-	
-	const FIRST_CYCLE: isize = 20;
-	const CYCLE_INTERVAL: isize = 40;
-
-	let mut cycle = 0;
-
-	for instruction in instructions {
-		for _ in 0..instruction.execution_time() {
-			cycle += 1;
-
-			match part {
-				Part::A => {
-					if (cycle - FIRST_CYCLE) % CYCLE_INTERVAL == 0 {
-						strength += cycle * x_reg;
-					}
-				},
-				Part::B => {
-					let position = (cycle - 1) % CYCLE_INTERVAL;
-
-					if isize::abs_diff(x_reg, position) < 2 {
-						print!("#");
-					} else {
-						print!(".");
-					}
-					
-					if cycle % CYCLE_INTERVAL == 0 {
-						println!();
-					}
-				},
-			}
-		}
+	Cpu::new().execute(&instructions, &mut [&mut strength, &mut crt]);
 
-		match instruction {
-			Instruction::NoOp => (),
-			Instruction::AddX(value) => x_reg += value,
-		}
-	}
+	match part {
+		Part::A => println!("{}", strength.total),
+		Part::B => match crt.read() {
+			Ok(text) => println!("{}", text),
+			// not every program draws letters, so show what it did draw instead
+			Err(_) => for row in crt.framebuffer() {
+				let line = row.iter()
+					.map(|lit| if *lit { '#' } else { '.' })
+					.collect::<String>();
 
-	if matches![part, Part::A] {
-		println!("{}", strength);
+				println!("{}", line);
+			},
+		},
 	}
 }
\ No newline at end of file
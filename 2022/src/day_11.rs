@@ -1,121 +1,265 @@
-use nom::{sequence::{tuple, preceded}, bytes::complete::tag, combinator::{map, map_res}, Parser, multi::separated_list0, character::complete::digit1};
+use std::str::Utf8Error;
 
-use crate::{Part, take_positive_number};
+use nom::{sequence::{tuple, preceded, delimited}, bytes::complete::tag, combinator::{map, map_res}, Parser, multi::{separated_list0, separated_list1}, character::complete::digit1};
+
+use crate::{Part, Input, take_positive_number};
 
 fn take_numbers<Num>(input: &str) -> nom::IResult<&str, Vec<Num>>
 where Num: std::str::FromStr{
 	separated_list0(tag(", "), map_res(digit1, str::parse))(input)
 }
 
+fn gcd(a: u128, b: u128) -> u128 {
+	if b == 0 { a } else { gcd(b, a % b) }
+}
+
+fn lcm(a: u128, b: u128) -> Option<u128> {
+	(a / gcd(a, b)).checked_mul(b)
+}
+
 #[derive(Debug, Clone)]
 enum Operation {
-	Add(isize),
-	Multiply(isize),
+	Add(u128),
+	Multiply(u128),
+	Doubled,
 	Squared,
 }
 
 impl Operation {
 	fn parse(input: &str) -> nom::IResult<&str, Self> {
-		preceded(tag("new = old "), 
+		preceded(tag("new = old "),
 			map(preceded(tag("* "), take_positive_number), Operation::Multiply)
 			.or(map(preceded(tag("+ "), take_positive_number), Operation::Add))
-			.or(map(tag("+ old"), |_| Operation::Multiply(2)))
+			.or(map(tag("+ old"), |_| Operation::Doubled))
 			.or(map(tag("* old"), |_| Operation::Squared))
 		)(input)
 	}
 
-	fn perform(&self, input: isize) -> isize {
+	fn perform(&self, input: u128) -> Option<u128> {
 		match self {
-			Self::Squared => input * input,
-			Self::Add(value) => input + value,
-			Self::Multiply(value) => input * value,
+			Self::Squared => input.checked_mul(input),
+			Self::Doubled => input.checked_add(input),
+			Self::Add(value) => input.checked_add(*value),
+			Self::Multiply(value) => input.checked_mul(*value),
 		}
 	}
 }
 
 #[derive(Debug, Clone)]
 struct Test {
-	number: isize,
+	number: u128,
 	value_true: usize,
 	value_false: usize,
 }
 
 #[derive(Debug)]
 struct Monkey {
-	id: isize,
-	items: Vec<isize>,
+	id: usize,
+	items: Vec<u128>,
 	operation: Operation,
 	test: Test,
 }
 
-pub(crate) fn solve(data: &[u8], part: Part) {
-	let monkeys = std::str::from_utf8(data).unwrap()
-		.split("\n\n");
-
-	let mut monkeys = monkeys.map(|i| {
-		let (i, (_, id, _)) =          tuple((tag("Monkey "), take_positive_number, tag(":\n")))(i).unwrap();
-		let (i, (_, items, _)) =       tuple((tag("  Starting items: "), take_numbers, tag("\n")))(i).unwrap();
-		let (i, (_, operation, _)) =   tuple((tag("  Operation: "), Operation::parse, tag("\n")))(i).unwrap();
-		let (i, (_, test_number, _)) = tuple((tag("  Test: divisible by "), take_positive_number, tag("\n")))(i).unwrap();
-		let (i, (_, test_true, _)) =   tuple((tag("    If true: throw to monkey "), take_positive_number, tag("\n")))(i).unwrap();
-		let (_, (_, test_false)) =     tuple((tag("    If false: throw to monkey "), take_positive_number))(i).unwrap();
-
-		let test = Test {
-			number: test_number,
-			value_true: test_true,
-			value_false: test_false,
-		};
-
-		Monkey { id, items, operation, test }
-	})
-	.collect::<Vec<_>>();
-
-	monkeys.sort_by(|a, b| a.id.cmp(&b.id));
-
-	let MAX_ROUNDS = match part {
-		Part::A => 20,
-		Part::B => 10000,
-	};
+impl Monkey {
+	fn parse(input: &str) -> nom::IResult<&str, Self> {
+		map(tuple((
+			delimited(tag("Monkey "), take_positive_number, tag(":\n")),
+			delimited(tag("  Starting items: "), take_numbers, tag("\n")),
+			delimited(tag("  Operation: "), Operation::parse, tag("\n")),
+			delimited(tag("  Test: divisible by "), take_positive_number, tag("\n")),
+			delimited(tag("    If true: throw to monkey "), take_positive_number, tag("\n")),
+			preceded(tag("    If false: throw to monkey "), take_positive_number),
+		)), |(id, items, operation, number, value_true, value_false)| {
+			let test = Test { number, value_true, value_false };
+			Self { id, items, operation, test }
+		})(input)
+	}
+}
 
-	let WORRY_DECAY = match part {
-		Part::A => 3,
-		Part::B => 1,
-	};
+#[derive(Debug)]
+pub(crate) enum MonkeyParseError {
+	Utf8Error(Utf8Error),
+	Malformed,
+	NoMonkeys,
+	ZeroDivisor(usize),
+	ModulusOverflow,
+}
+
+impl From<Utf8Error> for MonkeyParseError {
+	fn from(err: Utf8Error) -> Self {
+		Self::Utf8Error(err)
+	}
+}
+
+#[derive(Debug)]
+pub(crate) enum MonkeyError {
+	Overflow(usize),
+	UnknownMonkey(usize),
+	ZeroRelief,
+	ReliefOverflow,
+}
+
+#[derive(Debug)]
+pub(crate) struct MonkeyGroup {
+	monkeys: Vec<Monkey>,
+	relief: u128,
+	// the least common multiple of the test divisors
+	divisors: u128,
+	// a multiple of every test divisor and the relief, used to keep worry levels small
+	modulus: u128,
+	rounds: Vec<Vec<usize>>,
+}
+
+impl Input for MonkeyGroup {
+	type Error = MonkeyParseError;
+
+	fn parse_str(data: &str) -> Result<Self, Self::Error> {
+		let (rest, mut monkeys) = separated_list1(tag("\n\n"), Monkey::parse)(data)
+			.map_err(|_| MonkeyParseError::Malformed)?;
+
+		if !rest.trim().is_empty() {
+			return Err(MonkeyParseError::Malformed);
+		}
+
+		monkeys.sort_by_key(|m| m.id);
+
+		if let Some(monkey) = monkeys.iter().find(|m| m.test.number == 0) {
+			return Err(MonkeyParseError::ZeroDivisor(monkey.id));
+		}
+
+		let modulus = monkeys.iter()
+			.map(|m| Some(m.test.number))
+			.reduce(|a, b| lcm(a?, b?))
+			.ok_or(MonkeyParseError::NoMonkeys)?
+			.ok_or(MonkeyParseError::ModulusOverflow)?;
+
+		Ok(Self { monkeys, relief: 1, divisors: modulus, modulus, rounds: vec![] })
+	}
+}
+
+impl MonkeyGroup {
+	pub fn with_relief(mut self, relief: u128) -> Result<Self, MonkeyError> {
+		if relief == 0 {
+			return Err(MonkeyError::ZeroRelief);
+		}
 
-	let mut inspections = vec![0; monkeys.len()];
+		self.modulus = self.divisors.checked_mul(relief).ok_or(MonkeyError::ReliefOverflow)?;
+		self.relief = relief;
+		Ok(self)
+	}
 
-	// very simple gcd
-	let gcd: isize = monkeys.iter()
-		.map(|m| m.test.number)
-		.product();
+	pub fn round(&mut self) -> Result<(), MonkeyError> {
+		let mut inspections = vec![0; self.monkeys.len()];
 
-	for round in 1..=MAX_ROUNDS {
-		for monkey_id in 0..monkeys.len() {
-			let monkey = monkeys.get_mut(monkey_id).unwrap();
+		for (monkey_id, inspected) in inspections.iter_mut().enumerate() {
+			let monkey = &mut self.monkeys[monkey_id];
 
-			let items = monkey.items.clone();
-			inspections[monkey_id] += items.len();
-			monkey.items = vec![];
+			let items = std::mem::take(&mut monkey.items);
+			*inspected = items.len();
 			let op = monkey.operation.clone();
 			let test = monkey.test.clone();
 
-
 			for old in items {
-				let new = (op.perform(old) / WORRY_DECAY) % gcd;
+				// Reducing by a multiple of every divisor times the relief leaves the tests unchanged,
+				// as the level after dividing is then reduced by a multiple of every divisor.
+				let new = op.perform(old).ok_or(MonkeyError::Overflow(monkey_id))? % self.modulus / self.relief;
 
-				let pass_monkey = if new % test.number == 0 {
+				let pass_monkey = if new.is_multiple_of(test.number) {
 					test.value_true
 				} else {
 					test.value_false
 				};
 
-				monkeys.get_mut(pass_monkey).unwrap().items.push(new);
+				self.monkeys.get_mut(pass_monkey)
+					.ok_or(MonkeyError::UnknownMonkey(pass_monkey))?
+					.items.push(new);
 			}
 		}
+
+		self.rounds.push(inspections);
+
+		Ok(())
+	}
+
+	pub fn run(&mut self, rounds: usize) -> Result<(), MonkeyError> {
+		for _ in 0..rounds {
+			self.round()?;
+		}
+		Ok(())
+	}
+
+	// rounds are counted from 1
+	pub fn round_inspections(&self, round: usize) -> Option<&[usize]> {
+		self.rounds.get(round.checked_sub(1)?).map(Vec::as_slice)
+	}
+
+	pub fn inspections(&self) -> Vec<usize> {
+		let mut totals = vec![0; self.monkeys.len()];
+
+		for round in self.rounds.iter() {
+			for (total, count) in totals.iter_mut().zip(round) {
+				*total += count;
+			}
+		}
+
+		totals
+	}
+
+	pub fn monkey_business(&self) -> usize {
+		let mut inspections = self.inspections();
+		inspections.sort_unstable_by(|a, b| b.cmp(a));
+		inspections.iter().take(2).product()
 	}
+}
+
+pub(crate) fn solve(data: &[u8], part: Part) {
+	let (rounds, relief) = match part {
+		Part::A => (20, 3),
+		Part::B => (10000, 1),
+	};
+
+	let mut monkeys = MonkeyGroup::parse(data)
+		.expect("input parse error")
+		.with_relief(relief)
+		.expect("invalid relief");
+
+	monkeys.run(rounds).expect("simulation error");
+
+	println!("{}", monkeys.monkey_business());
+}
+#[cfg(test)]
+mod tests {
+	use super::*;
 
-	inspections.sort();
-	let monkey_business: usize = inspections.iter().rev().take(2).product();
+	const EXAMPLE: &str = include_str!("../inputs/11/example.txt");
 
-	println!("{}", monkey_business);
+	#[test]
+	fn example() {
+		let mut monkeys = MonkeyGroup::parse_str(EXAMPLE).unwrap().with_relief(3).unwrap();
+		monkeys.run(20).unwrap();
+		assert_eq!(monkeys.monkey_business(), 10605);
+		assert_eq!(monkeys.round_inspections(1), Some([2, 4, 3, 5].as_slice()));
+
+		let mut monkeys = MonkeyGroup::parse_str(EXAMPLE).unwrap();
+		monkeys.run(10000).unwrap();
+		assert_eq!(monkeys.monkey_business(), 2713310158);
+	}
+
+	#[test]
+	fn long_runs_with_relief() {
+		let mut monkeys = MonkeyGroup::parse_str(EXAMPLE).unwrap().with_relief(3).unwrap();
+		assert!(monkeys.run(10000).is_ok());
+	}
+
+	#[test]
+	fn bad_relief() {
+		assert!(matches!(MonkeyGroup::parse_str(EXAMPLE).unwrap().with_relief(0), Err(MonkeyError::ZeroRelief)));
+		assert!(matches!(MonkeyGroup::parse_str(EXAMPLE).unwrap().with_relief(u128::MAX), Err(MonkeyError::ReliefOverflow)));
+	}
+
+	#[test]
+	fn zero_divisor() {
+		let input = EXAMPLE.replace("divisible by 23", "divisible by 0");
+		assert!(matches!(MonkeyGroup::parse_str(&input), Err(MonkeyParseError::ZeroDivisor(0))));
+	}
 }
\ No newline at end of file
@@ -1,101 +1,156 @@
+use std::collections::VecDeque;
+
 use crate::Part;
 
-pub(crate) fn solve(data: &[u8], part: Part) {
+#[derive(Debug, Clone, Copy)]
+enum Node {
+	Start,
+	End,
+	Walkable(u8),
+}
 
-	enum Node {
-		Start,
-		End,
-		Walkable(i8),
+impl Node {
+	fn height(&self) -> u8 {
+		match self {
+			Node::End => b'z',
+			Node::Start => b'a',
+			Node::Walkable(v) => *v,
+		}
 	}
+}
 
-	impl Node {
-		fn height(&self) -> i8 {
-			match self {
-				Node::End => 'z' as i8,
-				Node::Start => 'a' as i8,
-				Node::Walkable(v) => *v,
-			}
-		}
+#[derive(Debug)]
+struct HeightMap {
+	width: usize,
+	height: usize,
+	nodes: Vec<Node>,
+}
+
+impl HeightMap {
+	fn parse(input: &str) -> Self {
+		let rows = input.split('\n')
+			.map(|line| {
+				line.bytes().map(|c| {
+					match c {
+						b'S' => Node::Start,
+						b'E' => Node::End,
+						c => Node::Walkable(c),
+					}
+				})
+				.collect::<Vec<_>>()
+			})
+			.collect::<Vec<_>>();
+
+		let height = rows.len();
+		let width = rows[0].len();
+		assert!(rows.iter().all(|r| r.len() == width), "map must be rectangular");
+
+		Self { width, height, nodes: rows.concat() }
 	}
-	
-	let map = std::str::from_utf8(data).unwrap()
-		.split('\n')
-		.map(|line| {
-			line.chars().map(|c| {
-				match c {
-					'S' => Node::Start,
-					'E' => Node::End,
-					c => Node::Walkable(c as i8),
-				}
+
+	fn position(&self, index: usize) -> (usize, usize) {
+		(index % self.width, index / self.width)
+	}
+
+	fn steps(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+		const OFFSETS: &[(isize, isize); 4] = &[
+			(1, 0),
+			(-1, 0),
+			(0, 1),
+			(0, -1),
+		];
+
+		let (x, y) = self.position(index);
+		let height = self.nodes[index].height();
+
+		OFFSETS.iter()
+			.filter_map(move |(ox, oy)| {
+				let ax = x.checked_add_signed(*ox).filter(|ax| *ax < self.width)?;
+				let ay = y.checked_add_signed(*oy).filter(|ay| *ay < self.height)?;
+				Some(ay * self.width + ax)
 			})
-			.collect()
-		})
-		.collect::<Vec<Vec<Node>>>();
-
-	let height = map.len();
-	let width = map[0].len();
-	
-	let mut graph = petgraph::Graph::<(), ()>::new();
-
-	const OFFSETS: &[(isize, isize); 4] = &[
-		(1, 0),
-		(-1, 0),
-		(0, 1),
-		(0, -1),
-	];
-
-	let ids = (0..width).into_iter().map(|x| {
-		(0..width).into_iter().map(|y| {
-			graph.add_node(())
-		}).collect()
-	}).collect::<Vec<Vec<petgraph::graph::NodeIndex>>>();
-
-	for x in 0..width {
-		for y in 0..height {
-			let node = &map[y][x];
-			let height = node.height();
-			let gid = ids[y][x];
-
-			for (ax, ay) in OFFSETS.iter().map(|(ox, oy)| (x as isize + ox, y as isize + oy)) {
-				if ax >= 0 && ay >= 0 {
-					let other = map.get(ay as usize).and_then(|v| v.get(ax as usize));
-					if let Some(other) = other {
-						if height - other.height() >= -1 {
-							let gido = ids[ay as usize][ax as usize];
-							graph.extend_with_edges(&[
-								(gid, gido),
-							])
-						}
-					}
+			.filter(move |other| self.nodes[*other].height() <= height + 1)
+	}
+
+	// the route includes both ends
+	fn shortest_path(&self, is_source: impl Fn(&Node) -> bool) -> Option<Vec<(usize, usize)>> {
+		let mut previous = vec![None; self.nodes.len()];
+		let mut seen = vec![false; self.nodes.len()];
+		let mut queue = VecDeque::new();
+
+		for (index, node) in self.nodes.iter().enumerate() {
+			if is_source(node) {
+				seen[index] = true;
+				queue.push_back(index);
+			}
+		}
+
+		while let Some(index) = queue.pop_front() {
+			if matches!(self.nodes[index], Node::End) {
+				let mut path = vec![self.position(index)];
+				let mut current = index;
+
+				while let Some(before) = previous[current] {
+					path.push(self.position(before));
+					current = before;
 				}
 
+				path.reverse();
+				return Some(path);
+			}
+
+			for next in self.steps(index) {
+				if !seen[next] {
+					seen[next] = true;
+					previous[next] = Some(index);
+					queue.push_back(next);
+				}
 			}
 		}
+
+		None
+	}
+
+	fn render_path(&self, path: &[(usize, usize)]) -> String {
+		let mut cells = self.nodes.iter()
+			.map(|node| match node {
+				Node::Start => 'S',
+				Node::End => 'E',
+				Node::Walkable(height) => *height as char,
+			})
+			.collect::<Vec<_>>();
+
+		for pair in path.windows(2) {
+			let [(x, y), (nx, ny)] = [pair[0], pair[1]];
+
+			cells[y * self.width + x] = match (nx as isize - x as isize, ny as isize - y as isize) {
+				(1, 0) => '>',
+				(-1, 0) => '<',
+				(0, 1) => 'v',
+				(0, -1) => '^',
+				_ => panic!("path steps must be between adjacent cells"),
+			};
+		}
+
+		cells.chunks(self.width)
+			.map(|row| row.iter().collect::<String>() + "\n")
+			.collect()
 	}
-	
-	let start = map.iter().flatten().position(|m| matches!(m, Node::Start)).unwrap();
-	let start = ids[start / width][start % width];
-
-	let end = map.iter().flatten().position(|m| matches!(m, Node::End)).unwrap();
-	let end = ids[end / width][end % width];
-
-	match part {
-		Part::A => {
-			let path = petgraph::algo::dijkstra(&graph, start, Some(end), |_| 1);
-			println!("{:?}", path.get(&end));
-		},	
-		Part::B => {
-			graph.reverse();
-			let path = petgraph::algo::dijkstra(&graph, end, None, |_| 1);
-			let lowest = map.iter()
-				.flatten()
-				.enumerate()
-				.filter(|(_, m)| m.height() == b'a' as i8)
-				.filter_map(|(id, _)| path.get(&ids[id / width][id % width]))
-				.copied()
-				.reduce(u32::min);
-
-			println!("{:?}", lowest);
-		},
+}
+
+pub(crate) fn solve(data: &[u8], part: Part, show_route: bool) {
+	let map = HeightMap::parse(std::str::from_utf8(data).unwrap());
+
+	let path = match part {
+		Part::A => map.shortest_path(|n| matches!(n, Node::Start)),
+		Part::B => map.shortest_path(|n| n.height() == b'a'),
+	};
+
+	let path = path.expect("no route to the end");
+
+	// the route is for explaining the answer, so keep it out of the way of the answer itself
+	if show_route {
+		eprint!("{}", map.render_path(&path));
 	}
+	println!("{}", path.len() - 1);
 }
\ No newline at end of file
@@ -1,45 +1,6 @@
 use std::cmp::Ordering;
 
-use nom::{sequence::delimited, combinator::map, bytes::complete::tag, multi::separated_list0};
-
-use crate::{Part, take_positive_number};
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum Value {
-	Int(u8),
-	List(Vec<Value>),
-}
-
-impl Value {
-	fn parse(input: &str) -> nom::IResult<&str, Self> {
-		let as_int = map(take_positive_number, Self::Int)(input);
-		let as_list = map(delimited(tag("["), separated_list0(tag(","), Value::parse), tag("]")), Self::List)(input);
-		as_int.or(as_list)
-	}
-}
-
-impl PartialOrd for Value {
-	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-		match (self, other) {
-			(Value::Int(a), Value::Int(b)) => a.partial_cmp(b),
-			(Value::Int(a), Value::List(_)) => {
-				Value::List(vec![Value::Int(*a)]).partial_cmp(other)
-			},
-			(Value::List(_), Value::Int(b)) => {
-				self.partial_cmp(&Value::List(vec![Value::Int(*b)]))
-			},
-			(Value::List(a), Value::List(b)) => {
-				a.partial_cmp(b)
-			},
-		}
-	}
-}
-
-impl Ord for Value {
-	fn cmp(&self, other: &Self) -> Ordering {
-		self.partial_cmp(other).unwrap()
-	}
-}
+use crate::{Part, packet::Packet};
 
 pub(crate) fn solve(data: &[u8], part: Part) {
 	match part {
@@ -48,7 +9,7 @@ pub(crate) fn solve(data: &[u8], part: Part) {
 				.split("\n\n")
 				.map(|p| {
 					let (a, b) = p.split_once('\n').unwrap();
-					(Value::parse(a).unwrap().1, Value::parse(b).unwrap().1)
+					(Packet::parse(a).unwrap().1, Packet::parse(b).unwrap().1)
 				})
 				.collect::<Vec<_>>();
 
@@ -62,24 +23,21 @@ pub(crate) fn solve(data: &[u8], part: Part) {
 		},
 		Part::B => {
 			let markers = [
-				Value::List(vec![Value::List(vec![Value::Int(2)])]),
-				Value::List(vec![Value::List(vec![Value::Int(6)])]),
+				Packet::parse("[[2]]").unwrap().1,
+				Packet::parse("[[6]]").unwrap().1,
 			];
 
-			let mut packets = std::str::from_utf8(data).unwrap()
+			let packets = std::str::from_utf8(data).unwrap()
 				.split('\n')
 				.filter(|s| !s.is_empty())
-				.map(|p| Value::parse(p).unwrap().1)
-				.chain(markers.clone())
+				.map(|p| Packet::parse(p).unwrap().1)
 				.collect::<Vec<_>>();
 
-			packets.sort();
-
+			// each marker is also placed after any smaller markers
 			let decoder_key = markers.iter()
-				.map(|m| packets.iter().position(|v| v.eq(m)).unwrap())
-				.map(|p| p + 1)
+				.map(|m| m.rank(&packets) + markers.iter().filter(|o| *o < m).count())
 				.product::<usize>();
-			
+
 			println!("{}", decoder_key);
 		},
 	}
@@ -19,15 +19,6 @@ impl std::fmt::Display for Tile {
 	}
 }
 
-fn draw_grid(grid: &Vec<Vec<Tile>>, width: usize, height: usize) {
-	for y in 0..height {
-		for x in 0..width {
-			print!("{}", grid[y][x]);
-		}
-		println!();
-	}
-}
-
 fn parse_path<Num>(input: &str) -> nom::IResult<&str, Vec<(Num, Num)>>
 where Num: std::str::FromStr {
 	separated_list0(tag(" -> "), tuple((take_positive_number, tag(","), take_positive_number)))(input)
@@ -38,115 +29,202 @@ where Num: std::str::FromStr {
 		})
 }
 
-pub(crate) fn solve(data: &[u8], part: Part) {	
+type Point = (isize, isize);
 
-	let paths = std::str::from_utf8(data).unwrap().split('\n')
-		.map(parse_path)
-		.map(Result::unwrap)
-		.map(|(_, path)| path)
-		.collect::<Vec<Vec<(usize, usize)>>>();
+// either axis aligned or at 45°
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Segment {
+	start: Point,
+	end: Point,
+}
 
-	const SAND_INPOINT: (usize, usize) = (500, 0);
+impl Segment {
+	fn points(&self) -> impl Iterator<Item = Point> {
+		let step = ((self.end.0 - self.start.0).signum(), (self.end.1 - self.start.1).signum());
+		let length = isize::max(
+			isize::abs(self.end.0 - self.start.0),
+			isize::abs(self.end.1 - self.start.1),
+		);
+		let start = self.start;
 
-	let mut min_x = *paths.iter().flatten().chain([&SAND_INPOINT]).map(|(x, _)| x).min().unwrap();
-	let min_y = *paths.iter().flatten().chain([&SAND_INPOINT]).map(|(_, y)| y).min().unwrap();
-	let mut max_x = *paths.iter().flatten().chain([&SAND_INPOINT]).map(|(x, _)| x).max().unwrap();
-	let max_y = *paths.iter().flatten().chain([&SAND_INPOINT]).map(|(_, y)| y).max().unwrap();
+		(0..=length).map(move |i| (start.0 + step.0 * i, start.1 + step.1 * i))
+	}
+}
 
-	let extra_height = match part {
-		Part::A => 0,
-		Part::B => 1,
-	};
-	
-	let height = max_y - min_y + 1 + extra_height;
+#[derive(Debug)]
+pub(crate) enum PathError {
+	Malformed(String),
+	Empty,
+	Skewed(Point, Point),
+	Diagonal(Point, Point),
+}
 
-	if matches!(part, Part::B) {
-		min_x = SAND_INPOINT.0 - height;
-		max_x = SAND_INPOINT.0 + height;
-	}
+#[derive(Debug)]
+pub(crate) struct RockError {
+	// counted from 1
+	line: usize,
+	error: PathError,
+}
 
-	let width = max_x - min_x + 1;
-
-	let mut grid = vec![vec![Tile::Empty; width]; height];
-
-	for path in paths {
-		for pair in path.windows(2) {
-			match pair {
-				[(x1, y1), (x2, y2)] => {
-					if x1 == x2 {
-						for y in *y1.min(y2)..=*y1.max(y2) {
-							grid[y - min_y][*x1 - min_x] = Tile::Stone;
-						}
-					} else if y1 == y2 {
-						for x in *x1.min(x2)..=*x1.max(x2) {
-							grid[*y1 - min_y][x - min_x] = Tile::Stone;
-						}
-					} else {
-						unimplemented!();
+// a path of a single point is a single piece of rock
+pub(crate) fn parse_rocks(input: &str, allow_diagonals: bool) -> Result<Vec<Segment>, RockError> {
+	let mut segments = vec![];
+
+	for (index, line) in input.split('\n').enumerate() {
+		let fail = |error| RockError { line: index + 1, error };
+
+		let path = match parse_path::<isize>(line) {
+			Ok(("", path)) => path,
+			_ => return Err(fail(PathError::Malformed(line.to_owned()))),
+		};
+
+		match path.as_slice() {
+			[] => return Err(fail(PathError::Empty)),
+			[point] => segments.push(Segment { start: *point, end: *point }),
+			path => for pair in path.windows(2) {
+				let (start, end) = (pair[0], pair[1]);
+				let dx = isize::abs_diff(start.0, end.0);
+				let dy = isize::abs_diff(start.1, end.1);
+
+				if dx != 0 && dy != 0 {
+					if dx != dy {
+						return Err(fail(PathError::Skewed(start, end)));
+					} else if !allow_diagonals {
+						return Err(fail(PathError::Diagonal(start, end)));
 					}
+				}
+
+				segments.push(Segment { start, end });
+			},
+		}
+	}
+
+	Ok(segments)
+}
+
+const SAND_INPOINT: Point = (500, 0);
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Floor {
+	Abyss,
+	// two below the lowest rock
+	Solid,
+}
+
+#[derive(Debug)]
+pub(crate) struct Cave {
+	min_x: isize,
+	width: usize,
+	// the last row sand can come to rest in, with the floor (if any) just below
+	max_y: isize,
+	floor: Floor,
+	grid: Vec<Tile>,
+}
+
+impl Cave {
+	pub fn new(rocks: &[Segment], floor: Floor) -> Self {
+		let points = rocks.iter().flat_map(|s| [s.start, s.end]);
+
+		let lowest = points.clone()
+			.map(|(_, y)| y)
+			.max()
+			.unwrap_or(SAND_INPOINT.1);
+
+		let max_y = lowest + 1;
+
+		// sand can spread at most one column per row, so never gets further than this
+		let reach = max_y - SAND_INPOINT.1 + 1;
+		let min_x = points.clone().map(|(x, _)| x)
+			.chain([SAND_INPOINT.0 - reach])
+			.min().unwrap();
+		let max_x = points.map(|(x, _)| x)
+			.chain([SAND_INPOINT.0 + reach])
+			.max().unwrap();
+
+		let width = (max_x - min_x + 1) as usize;
+		let height = (max_y + 1) as usize;
+
+		let mut cave = Self {
+			min_x,
+			width,
+			max_y,
+			floor,
+			grid: vec![Tile::Empty; width * height],
+		};
+
+		for point in rocks.iter().flat_map(Segment::points) {
+			cave.set(point, Tile::Stone);
+		}
+
+		cave
+	}
+
+	fn index(&self, (x, y): Point) -> usize {
+		y as usize * self.width + (x - self.min_x) as usize
+	}
+
+	fn set(&mut self, pos: Point, tile: Tile) {
+		let index = self.index(pos);
+		self.grid[index] = tile;
+	}
+
+	fn fall(&self, pos: Point) -> Option<Point> {
+		const SAND_MOTION: [(isize, isize); 3] = [(0, 1), (-1, 1), (1, 1)];
+
+		SAND_MOTION.iter()
+			.map(|(x, y)| (pos.0 + x, pos.1 + y))
+			.find(|next| {
+				// only a solid floor stops sand below the last row
+				next.1 > self.max_y && matches!(self.floor, Floor::Abyss)
+				|| next.1 <= self.max_y && matches!(self.grid[self.index(*next)], Tile::Empty)
+			})
+	}
+
+	pub fn fill(&mut self) -> usize {
+		// Each grain follows the path of the one before until the point it landed,
+		// so only the remainder of the path needs simulating.
+		let mut path = vec![SAND_INPOINT];
+		let mut sands = 0;
+
+		while let Some(pos) = path.last().copied() {
+			match self.fall(pos) {
+				Some(next) if next.1 > self.max_y => break,
+				Some(next) => path.push(next),
+				None => {
+					self.set(pos, Tile::Sand);
+					sands += 1;
+					path.pop();
 				},
-				_ => panic!(),
 			}
-			
 		}
+
+		sands
 	}
+}
 
-	const SAND_MOTION: [(isize, isize); 3] = [(0, 1), (-1, 1), (1, 1)];
-	
-	let mut sands = 0;
-	let mut falling = true;
-	
-	let initial_point = (
-		(SAND_INPOINT.0 - min_x) as isize,
-		(SAND_INPOINT.1 - min_y) as isize
-	);
-
-	while falling {
-		sands += 1;
-
-		let mut pos = initial_point;
-
-		while let Some(next_pos) = SAND_MOTION.iter().find_map(|(x, y)| {
-			if (pos.1 + y) >= 0 && pos.0 + x >= 0 {
-				let next_pos = (pos.0 + x, pos.1 + y);
-				
-				let tile = grid.get(next_pos.1 as usize)
-					.and_then(|row| row.get(next_pos.0 as usize));
-
-				match tile {
-					None => {
-						if matches!(part, Part::A) {
-							falling = false;
-						}
-						None
-					},
-					Some(Tile::Empty) => {
-						Some(next_pos)
-					},
-					_ => None,
-				}
-			} else {
-				falling = false;
-				match part {
-					Part::A => None,
-					Part::B => unimplemented!("requires larger grid"),
-				}
+impl std::fmt::Display for Cave {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		for row in self.grid.chunks(self.width) {
+			for tile in row {
+				write!(f, "{}", tile)?;
 			}
-		}) {
-			pos = next_pos;
+			writeln!(f)?;
 		}
+		Ok(())
+	}
+}
 
-		if pos == initial_point {
-			falling = false;
-		}
+pub(crate) fn solve(data: &[u8], part: Part) {
 
-		grid[pos.1 as usize][pos.0 as usize] = Tile::Sand;
-	}
-	
-	draw_grid(&grid, width, height);
+	let rocks = parse_rocks(std::str::from_utf8(data).unwrap(), false)
+		.expect("input parse error");
+
+	let floor = match part {
+		Part::A => Floor::Abyss,
+		Part::B => Floor::Solid,
+	};
+
+	let mut cave = Cave::new(&rocks, floor);
 
-	match part {
-		Part::A => println!("{}", sands - 1),
-		Part::B => println!("{}", sands),
-	}		
+	println!("{}", cave.fill());
 }
\ No newline at end of file
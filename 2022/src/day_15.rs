@@ -1,6 +1,8 @@
+use std::collections::HashSet;
+
 use nom::{combinator::map, sequence::{tuple, preceded}, bytes::complete::tag};
 
-use crate::{Part, take_number};
+use crate::{Part, take_number, interval::{Interval, IntervalSet}};
 
 #[derive(Debug)]
 struct Sensor {
@@ -22,6 +24,18 @@ impl Sensor {
 	fn covers(&self, point: (isize, isize)) -> bool {
 		self.range >= man_distance(self.pos, point)
 	}
+
+	fn coverage(&self, y: isize) -> Option<Interval<isize>> {
+		let reach = self.range as isize - isize::abs_diff(self.pos.1, y) as isize;
+		Interval::new(self.pos.0 - reach, self.pos.0 + reach)
+	}
+
+	// the constants `c` of the lines `x + y = c` and `x - y = c` just outside the range
+	fn perimeter_lines(&self) -> ([isize; 2], [isize; 2]) {
+		let reach = self.range as isize + 1;
+		let (x, y) = self.pos;
+		([x + y - reach, x + y + reach], [x - y - reach, x - y + reach])
+	}
 }
 
 fn take_coord<Num>(input: &str) -> nom::IResult<&str, (Num, Num)>
@@ -36,6 +50,33 @@ fn man_distance(a: (isize, isize), b: (isize, isize)) -> usize {
 	isize::abs_diff(a.0, b.0) + isize::abs_diff(a.1, b.1)
 }
 
+fn tuning_frequency(sensors: &[Sensor], bound: isize) -> Option<isize> {
+	// a lone uncovered position must sit just outside the range of its neighbours' sensors,
+	// so it lies where those perimeter lines cross each other or the edge of the area
+	let sums = sensors.iter()
+		.flat_map(|s| s.perimeter_lines().0)
+		.collect::<Vec<_>>();
+	let differences = sensors.iter()
+		.flat_map(|s| s.perimeter_lines().1)
+		.collect::<Vec<_>>();
+
+	let crossings = sums.iter().flat_map(|a| {
+		differences.iter()
+			.filter(move |d| (a + *d) % 2 == 0)
+			.map(move |d| ((a + d) / 2, (a - d) / 2))
+	});
+
+	let edges = sums.iter()
+		.flat_map(|a| [(0, *a), (bound, a - bound), (*a, 0), (a - bound, bound)])
+		.chain(differences.iter().flat_map(|d| [(0, -d), (bound, bound - d), (*d, 0), (d + bound, bound)]))
+		.chain([(0, 0), (0, bound), (bound, 0), (bound, bound)]);
+
+	crossings.chain(edges)
+		.filter(|(x, y)| (0..=bound).contains(x) && (0..=bound).contains(y))
+		.find(|p| !sensors.iter().any(|s| s.covers(*p)))
+		.map(|(x, y)| x * 4_000_000 + y)
+}
+
 pub(crate) fn solve(data: &[u8], part: Part, is_example: bool) {
 
 	let sensors = std::str::from_utf8(data).unwrap()
@@ -45,91 +86,26 @@ pub(crate) fn solve(data: &[u8], part: Part, is_example: bool) {
 
 	match part {
 		Part::A => {
-			let min_x = sensors.iter().map(|s| s.pos.0 - s.range as isize).min().unwrap();
-			let max_x = sensors.iter().map(|s| s.pos.0 + s.range as isize).max().unwrap();
-
 			let y = if is_example {10} else {2000000};
-			let sensors = sensors.iter().filter(|s| s.range >= isize::abs_diff(s.pos.1, y));
-			
-			let covered = (min_x..=max_x).into_iter().filter(|x| {
-				let is_covered = sensors.clone().any(|s| s.range >= man_distance(s.pos, (*x, y)));
-				let is_beacon = sensors.clone().any(|s| s.beacon.0 == *x && s.beacon.1 == y);
-				//match (is_covered, is_beacon) {
-				//	(_, true) => print!("B"),
-				//	(true, false) => print!("#"),
-				//	(false, false) => print!("."),
-				//}
-				!is_beacon && is_covered
-			}).count();
-
-			//println!();
-			println!("{}", covered);
+
+			let covered = sensors.iter()
+				.filter_map(|s| s.coverage(y))
+				.collect::<IntervalSet<_>>();
+
+			let beacons = sensors.iter()
+				.map(|s| s.beacon)
+				.filter(|b| b.1 == y && covered.contains(b.0))
+				.collect::<HashSet<_>>();
+
+			let covered_len = covered.len().expect("row coverage too long to count");
+			println!("{}", covered_len - beacons.len() as isize);
 		},
 		Part::B => {
-			fn all_covered(sensors: &[Sensor], point: &[(isize, isize)]) -> bool {
-				sensors.iter().any(|s| {
-					point.iter().all(|p| s.covers(*p))
-				})
-			}
-			
-			fn search_area(
-				sensors: &[Sensor],
-				start: (isize, isize),
-				end: (isize, isize),
-			) -> Option<(isize, isize)> {
-				//println!("searching: x({}–{}), y({}–{})", start.0, end.0, start.1, end.1);
-
-				if start.0 > end.0 || start.1 > end.1 {
-					return None;
-				}
-
-				if start == end {
-					if sensors.iter().any(|s| s.covers(start)) {
-						None
-					} else {
-						Some(start)
-					}
-				} else {
-					let covered = all_covered(sensors, &[start, end, (start.0, end.1), (end.0, start.1)]);
-					
-					if covered {
-						None
-					} else {
-						let mid = ((start.0 + end.0) / 2, (start.1 + end.1) / 2);
-
-						search_area(sensors, start, mid)
-						.or_else(|| search_area(sensors, (mid.0 + 1, start.1), (end.0, mid.1 + 1)))
-						.or_else(|| search_area(sensors, (start.0, mid.1 + 1), (mid.0 + 1, end.1)))
-						.or_else(|| search_area(sensors, (mid.0 + 1, mid.1 + 1), end))
-					}
-				}
-			}
-
-
-			let min = 0;
 			let max = if is_example {20} else {4000000};
 
-			//for y in min..=max {
-			//	for x in min..=max {
-			//		let is_sensor = sensors.iter().any(|s| s.pos == (x, y));
-			//		let is_covered = sensors.iter().any(|s| s.covers((x, y)));
-			//		let is_beacon = sensors.iter().any(|s| s.beacon == (x, y));
-			//		if is_sensor {
-			//			print!("S");
-			//		} else if is_beacon {
-			//			print!("B");
-			//		} else if is_covered {
-			//			print!("#");
-			//		} else {
-			//			print!(".");
-			//		}
-			//	}
-			//	println!();
-			//}
-
-			let tuning_freq = search_area(&sensors, (min, min), (max, max));
-
-			println!("{}, {:?} {:?}", sensors.iter().any(|s| s.covers(tuning_freq.unwrap())), tuning_freq, tuning_freq.map(|(x, y)| x * 4000000 + y));
+			let tuning_freq = tuning_frequency(&sensors, max).expect("no uncovered position");
+
+			println!("{}", tuning_freq);
 		},
 	};
 }
\ No newline at end of file
@@ -1,9 +1,6 @@
-use std::collections::HashMap;
-
 use nom::{combinator::map, sequence::{preceded, tuple}, bytes::complete::tag, character::complete::alpha1, multi::separated_list0, Parser};
-use petgraph::prelude::NodeIndex;
 
-use crate::{Part, take_positive_number};
+use crate::{Part, take_positive_number, distance_matrix::DistanceMatrix};
 
 #[derive(Debug)]
 struct Valve {
@@ -26,11 +23,6 @@ impl Valve {
 	}
 }
 
-struct IndexedValve {
-	flow: usize,
-	tunnels: Vec<usize>,
-}
-
 pub(crate) fn solve(data: &[u8], part: Part) {
 
 	let valves = std::str::from_utf8(data).unwrap()
@@ -40,12 +32,10 @@ pub(crate) fn solve(data: &[u8], part: Part) {
 
 	let starting_location = valves.iter().position(|v| v.id == "AA").unwrap();
 
-	let valves = valves.iter().map(|v| {
-		let tunnels = v.tunnels.iter()
+	let tunnels = valves.iter().map(|v| {
+		v.tunnels.iter()
 			.map(|t| valves.iter().position(|v| &v.id == t).unwrap())
-			.collect::<Vec<usize>>();
-
-		IndexedValve { flow: v.flow, tunnels }
+			.collect::<Vec<usize>>()
 	}).collect::<Vec<_>>();
 
 	let time_budget = match part {
@@ -53,69 +43,62 @@ pub(crate) fn solve(data: &[u8], part: Part) {
 		Part::B => 26,
 	};
 
-
-	let mut graph = petgraph::Graph::<usize, _>::new();
-
-	let indexes = valves.iter()
-		.map(|v| graph.add_node(v.flow))
+	// only the start and valves worth opening matter to the search,
+	// so they get compact ids with the start as 0
+	let relevant = std::iter::once(starting_location)
+		.chain((0..valves.len()).filter(|v| *v != starting_location && valves[*v].flow > 0))
 		.collect::<Vec<_>>();
 
-	for (i, index) in indexes.iter().enumerate() {
-		for edge in valves[i].tunnels.iter() {
-			graph.add_edge(*index, indexes[*edge], ());
-		}
-	}
+	let cost_table = DistanceMatrix::from_edges(&tunnels)
+		.expect("tunnels too long for distance matrix")
+		.select(&relevant);
 
-	let cost_table = indexes.iter().map(|i| {
-		(*i, petgraph::algo::k_shortest_path(&graph, *i, None, 1, |_| 1))
-	}).collect::<HashMap<_, _>>();
+	let flows = relevant.iter()
+		.map(|v| valves[*v].flow)
+		.collect::<Vec<_>>();
 
-	let valuable_valves = indexes.iter()
-		.filter(|i| *graph.node_weight(**i).unwrap() > 0)
-		.copied()
+	let valuable_valves = (0..relevant.len())
+		.filter(|v| flows[*v] > 0)
 		.collect::<Vec<_>>();
 
 	fn best_flow(
-		graph: &petgraph::Graph::<usize, ()>,
-		cost_table: &HashMap<NodeIndex, HashMap<NodeIndex, usize>>,
-		location: NodeIndex,
-		valuable_valves: Vec<NodeIndex>,
+		flows: &[usize],
+		cost_table: &DistanceMatrix,
+		location: usize,
+		valuable_valves: &[usize],
 		time_remaining: usize,
 	) -> usize {
-		let value = graph.node_weight(location).unwrap() * time_remaining;
-		let valuable_valves = valuable_valves.into_iter()
-			.filter(|v| *v != location)
-			.collect::<Vec<_>>();
-
-		let costs = cost_table.get(&location).unwrap();
-		let best_subtree_value = valuable_valves.iter()
-			.filter_map(|v| {
-				let cost = *costs.get(v).unwrap();
-				if cost < (time_remaining - 1) {
-					Some((v, cost))
-				} else {
-					None
-				}
+		valuable_valves.iter()
+			.filter_map(|valve| {
+				// cost + 1 to account for then turning the valve
+				let cost = cost_table.get(location, *valve)? as usize + 1;
+				(cost < time_remaining).then_some((valve, cost))
 			})
-			.map(|(v, cost)| {
-				best_flow(
-					graph,
+			.map(|(valve, cost)| {
+				let time = time_remaining - cost;
+				let value = flows[*valve] * time;
+
+				let valuable_valves = valuable_valves.iter()
+					.filter(|v| *v != valve)
+					.copied()
+					.collect::<Vec<_>>();
+
+				value + best_flow(
+					flows,
 					cost_table,
-					*v,
-					valuable_valves.clone(),
-					time_remaining - cost - 1,
+					*valve,
+					&valuable_valves,
+					time,
 				)
 			})
-			.max().unwrap_or(0);
-
-		value + best_subtree_value
+			.max().unwrap_or(0)
 	}
 
 	fn best_flow_pair(
-		graph: &petgraph::Graph::<usize, ()>,
-		cost_table: &HashMap<NodeIndex, HashMap<NodeIndex, usize>>,
-		location: (NodeIndex, NodeIndex),
-		valuable_valves: Vec<NodeIndex>,
+		flows: &[usize],
+		cost_table: &DistanceMatrix,
+		location: (usize, usize),
+		valuable_valves: &[usize],
 		time_remaining: (usize, usize),
 	) -> usize {
 		// if b is ahead, we should work on a, otherwise b
@@ -124,37 +107,31 @@ pub(crate) fn solve(data: &[u8], part: Part) {
 		let used_location = if b_ahead { location.0 } else { location.1 };
 		let used_time_remaining = if b_ahead { time_remaining.0 } else { time_remaining.1 };
 
-		let costs = cost_table.get(&used_location).unwrap();
-
 		valuable_valves.iter()
 			.filter_map(|valve| {
 				// cost + 1 to account for then turning the valve
-				let cost = *costs.get(valve).unwrap() + 1;
-				if cost < used_time_remaining {
-					Some((valve, cost))
-				} else {
-					None
-				}
+				let cost = cost_table.get(used_location, *valve)? as usize + 1;
+				(cost < used_time_remaining).then_some((valve, cost))
 			})
 			.map(|(valve, cost)| {
 				let time = used_time_remaining - cost;
-				let value = graph.node_weight(*valve).unwrap() * time;
+				let value = flows[*valve] * time;
 
 				let valuable_valves = valuable_valves.iter()
 					.filter(|v| *v != valve)
-					.cloned()
+					.copied()
 					.collect::<Vec<_>>();
 
 
 				value + best_flow_pair(
-					graph,
+					flows,
 					cost_table,
 					if b_ahead {
 						(*valve, location.1)
 					} else {
 						(location.0, *valve)
 					},
-					valuable_valves,
+					&valuable_valves,
 					if b_ahead {
 						(time, time_remaining.1)
 					} else {
@@ -168,10 +145,10 @@ pub(crate) fn solve(data: &[u8], part: Part) {
 	match part {
 		Part::A => {
 			let best_pressure = best_flow(
-				&graph,
+				&flows,
 				&cost_table,
-				indexes[starting_location],
-				valuable_valves,
+				0,
+				&valuable_valves,
 				time_budget,
 			);
 
@@ -179,10 +156,10 @@ pub(crate) fn solve(data: &[u8], part: Part) {
 		},
 		Part::B => {
 			let best_pressure = best_flow_pair(
-				&graph,
+				&flows,
 				&cost_table,
-				(indexes[starting_location], indexes[starting_location]),
-				valuable_valves,
+				(0, 0),
+				&valuable_valves,
 				(time_budget, time_budget),
 			);
 
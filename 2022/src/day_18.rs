@@ -1,67 +1,240 @@
-use std::collections::HashSet;
+use std::collections::{VecDeque, HashMap, hash_map::Entry};
+use std::io::Write;
+use std::path::Path;
 
 use nom::{bytes::complete::tag, sequence::tuple};
 
 use crate::{Part, take_positive_number};
 
+type Voxel = (u32, u32, u32);
+
+// for each side of a voxel, its outward normal and its corners anticlockwise as seen from outside
+const SIDES: [((i8, i8, i8), [Voxel; 4]); 6] = [
+	((-1, 0, 0), [(0, 0, 0), (0, 0, 1), (0, 1, 1), (0, 1, 0)]),
+	((1, 0, 0), [(1, 0, 0), (1, 1, 0), (1, 1, 1), (1, 0, 1)]),
+	((0, -1, 0), [(0, 0, 0), (1, 0, 0), (1, 0, 1), (0, 0, 1)]),
+	((0, 1, 0), [(0, 1, 0), (0, 1, 1), (1, 1, 1), (1, 1, 0)]),
+	((0, 0, -1), [(0, 0, 0), (0, 1, 0), (1, 1, 0), (1, 0, 0)]),
+	((0, 0, 1), [(0, 0, 1), (1, 0, 1), (1, 1, 1), (0, 1, 1)]),
+];
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum MeshFormat {
+	Obj,
+	// ASCII rather than binary
+	Stl,
+}
+
+impl MeshFormat {
+	fn from_path(path: &Path) -> Option<Self> {
+		match path.extension()?.to_str()? {
+			"obj" => Some(Self::Obj),
+			"stl" => Some(Self::Stl),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Clone)]
 struct Grid {
 	width: u32,
 	height: u32,
 	depth: u32,
-	voxels: HashSet<u128>,
+	bits: Vec<u64>,
 }
 
 impl Grid {
 	fn new(width: u32, height: u32, depth: u32) -> Self {
+		let volume = width as usize * height as usize * depth as usize;
+
 		Self {
 			width,
 			height,
 			depth,
-			voxels: HashSet::new(),
+			bits: vec![0; volume.div_ceil(64)],
 		}
 	}
 
-	fn address(&self, x: u32, y: u32, z: u32) -> u128 {
-		x as u128
-		| (y as u128) << 32
-		| (z as u128) << 64
-	} 
-
-	fn unaddress(&self, address: u128) -> (u32, u32, u32) {
-		(
-			(address & 0xffffffff) as u32,
-			((address >> 32) & 0xffffffff) as u32,
-			((address >> 64) & 0xffffffff) as u32,
-		)
+	fn address(&self, (x, y, z): Voxel) -> usize {
+		(z as usize * self.height as usize + y as usize) * self.width as usize + x as usize
 	}
 
-	fn adjacent(&self, x: u32, y: u32, z: u32) -> [Option<(u32, u32, u32)>; 6] {
+	// in the same order as `SIDES`
+	fn adjacent(&self, (x, y, z): Voxel) -> [Option<Voxel>; 6] {
 		[
-			(x > 0).then_some((x - 1, y, z)),
-			(x < self.height - 1).then_some((x + 1, y, z)),
-			(y > 0).then_some((x, y - 1, z)),
-			(y < self.width - 1).then_some((x, y + 1, z)),
-			(z > 0).then_some((x, y, z - 1)),
+			(x > 0).then(|| (x - 1, y, z)),
+			(x < self.width - 1).then_some((x + 1, y, z)),
+			(y > 0).then(|| (x, y - 1, z)),
+			(y < self.height - 1).then_some((x, y + 1, z)),
+			(z > 0).then(|| (x, y, z - 1)),
 			(z < self.depth - 1).then_some((x, y, z + 1)),
 		]
 	}
 
-	fn adjacent_count(&self, x: u32, y: u32, z: u32) -> usize {
-		self.adjacent(x, y, z)
-			.into_iter()
-			.flatten()
-			.filter(|(x, y, z)| {
-				self.voxels.contains(&self.address(*x, *y, *z))
+	fn contains(&self, voxel: Voxel) -> bool {
+		let address = self.address(voxel);
+		self.bits[address / 64] & (1 << (address % 64)) != 0
+	}
+
+	fn set(&mut self, voxel: Voxel) {
+		let address = self.address(voxel);
+		self.bits[address / 64] |= 1 << (address % 64);
+	}
+
+	fn voxels(&self) -> impl Iterator<Item = Voxel> + '_ {
+		(0..self.depth).flat_map(move |z| {
+			(0..self.height).flat_map(move |y| {
+				(0..self.width).map(move |x| (x, y, z))
 			})
-			.count()
+		})
 	}
 
-	fn set(&mut self, x: u32, y: u32, z: u32) {
-		self.voxels.insert(self.address(x, y, z));
+	fn flood_fill(walls: &Grid, start: Voxel) -> Grid {
+		let mut filled = Grid::new(walls.width, walls.height, walls.depth);
+		let mut queue = VecDeque::from([start]);
+		filled.set(start);
+
+		while let Some(voxel) = queue.pop_front() {
+			for next in walls.adjacent(voxel).into_iter().flatten() {
+				if !walls.contains(next) && !filled.contains(next) {
+					filled.set(next);
+					queue.push_back(next);
+				}
+			}
+		}
+
+		filled
 	}
 }
 
-pub(crate) fn solve(data: &[u8], part: Part) {
+struct Droplet {
+	lava: Grid,
+	// the air connected to the outside, which always includes the corner at the origin
+	exterior: Grid,
+}
+
+impl Droplet {
+	fn new(positions: &[Voxel]) -> Self {
+		// leave a layer of air on every side so the exterior is all connected
+		let width = positions.iter().map(|(x, _, _)| *x).max().unwrap_or(0) + 2;
+		let height = positions.iter().map(|(_, y, _)| *y).max().unwrap_or(0) + 2;
+		let depth = positions.iter().map(|(_, _, z)| *z).max().unwrap_or(0) + 2;
+
+		let mut lava = Grid::new(width, height, depth);
+		for position in positions {
+			lava.set(*position);
+		}
+
+		let exterior = Grid::flood_fill(&lava, (0, 0, 0));
+
+		Self { lava, exterior }
+	}
+
+	// each face is given as the lava voxel and an index into `SIDES`
+	fn faces<'a>(&'a self, is_open: impl Fn(Voxel) -> bool + 'a) -> impl Iterator<Item = (Voxel, usize)> + 'a {
+		self.lava.voxels()
+			.filter(|v| self.lava.contains(*v))
+			.flat_map(move |v| {
+				self.lava.adjacent(v)
+					.into_iter()
+					.enumerate()
+					.filter_map(move |(side, adjacent)| Some((v, side, adjacent?)))
+			})
+			.filter(move |(_, _, adjacent)| is_open(*adjacent))
+			.map(|(v, side, _)| (v, side))
+	}
+
+	fn surface_area(&self) -> usize {
+		self.faces(|v| !self.lava.contains(v)).count()
+	}
+
+	fn exterior_surface_area(&self) -> usize {
+		self.faces(|v| self.exterior.contains(v)).count()
+	}
+
+	fn export_mesh<W: Write>(&self, mut writer: W, format: MeshFormat, exterior_only: bool) -> std::io::Result<()> {
+		let faces = if exterior_only {
+			self.faces(|v| self.exterior.contains(v)).collect::<Vec<_>>()
+		} else {
+			self.faces(|v| !self.lava.contains(v)).collect::<Vec<_>>()
+		};
+
+		// undo the layer of padding added when parsing
+		let corners = |(x, y, z): Voxel, side: usize| {
+			SIDES[side].1.map(|(cx, cy, cz)| (x + cx - 1, y + cy - 1, z + cz - 1))
+		};
+
+		match format {
+			MeshFormat::Obj => {
+				let mut vertices = HashMap::new();
+
+				for (voxel, side) in faces {
+					let mut indices = [0; 4];
+
+					for (index, corner) in indices.iter_mut().zip(corners(voxel, side)) {
+						let next = vertices.len() + 1;
+						*index = match vertices.entry(corner) {
+							Entry::Occupied(entry) => *entry.get(),
+							Entry::Vacant(entry) => {
+								writeln!(writer, "v {} {} {}", corner.0, corner.1, corner.2)?;
+								*entry.insert(next)
+							},
+						};
+					}
+
+					writeln!(writer, "f {} {} {} {}", indices[0], indices[1], indices[2], indices[3])?;
+				}
+			},
+			MeshFormat::Stl => {
+				writeln!(writer, "solid droplet")?;
+
+				for (voxel, side) in faces {
+					let (nx, ny, nz) = SIDES[side].0;
+					let [a, b, c, d] = corners(voxel, side);
+
+					for triangle in [[a, b, c], [a, c, d]] {
+						writeln!(writer, "facet normal {} {} {}", nx, ny, nz)?;
+						writeln!(writer, "  outer loop")?;
+						for (x, y, z) in triangle {
+							writeln!(writer, "    vertex {} {} {}", x, y, z)?;
+						}
+						writeln!(writer, "  endloop")?;
+						writeln!(writer, "endfacet")?;
+					}
+				}
+
+				writeln!(writer, "endsolid droplet")?;
+			},
+		}
+
+		Ok(())
+	}
+
+	fn air_pockets(&self) -> Vec<usize> {
+		let mut enclosed = self.lava.clone();
+		for (bits, exterior) in enclosed.bits.iter_mut().zip(self.exterior.bits.iter()) {
+			*bits |= exterior;
+		}
+
+		let mut pockets = vec![];
+
+		for voxel in self.lava.voxels() {
+			if !enclosed.contains(voxel) {
+				let pocket = Grid::flood_fill(&enclosed, voxel);
+
+				for (bits, pocket) in enclosed.bits.iter_mut().zip(pocket.bits.iter()) {
+					*bits |= pocket;
+				}
+
+				pockets.push(pocket.bits.iter().map(|b| b.count_ones() as usize).sum());
+			}
+		}
+
+		pockets
+	}
+}
+
+pub(crate) fn solve(data: &[u8], part: Part, mesh_path: Option<&Path>, mesh_exterior: bool) {
 	let positions = std::str::from_utf8(data).unwrap().split('\n')
 		.map(|l| {
 			let (_, (x, _, y, _, z)) = tuple((
@@ -72,50 +245,24 @@ pub(crate) fn solve(data: &[u8], part: Part) {
 
 			(x + 1, y + 1, z + 1)
 		})
-		.collect::<Vec<(u32, u32, u32)>>();
-
-	let width = *positions.iter().map(|(x, _, _)| x).max().unwrap() + 2;
-	let height = *positions.iter().map(|(_, y, _)| y).max().unwrap() + 2;
-	let depth = *positions.iter().map(|(_, _, z)| z).max().unwrap() + 2;
-
-	let mut grid = Grid::new(width, height, depth);
+		.collect::<Vec<Voxel>>();
 
-	let mut sides = 0;
+	let droplet = Droplet::new(&positions);
 
+	if let Some(path) = mesh_path {
+		let format = MeshFormat::from_path(path).expect("mesh path should end in .obj or .stl");
+		let file = std::io::BufWriter::new(std::fs::File::create(path).expect("invalid mesh path"));
 
-	for (x, y, z) in positions {
-		sides += 6 - 2 * grid.adjacent_count(x, y, z) as i32;
-		grid.set(x, y, z);
+		droplet.export_mesh(file, format, mesh_exterior).expect("mesh export error");
 	}
 
 	match part {
-		Part::A => println!("{}", sides),
+		Part::A => println!("{}", droplet.surface_area()),
 		Part::B => {
-			let mut visited_tracker = Grid::new(width, height, depth);
-			let mut visiting_tracker = Grid::new(width, height, depth);
-
-			visiting_tracker.set(0, 0, 0);
-
-			let mut faces = 0;
-
-			while let Some((x, y, z)) = visiting_tracker.voxels.iter()
-			.copied()
-			.find(|a| !visited_tracker.voxels.contains(a))
-			.map(|a| visiting_tracker.unaddress(a))
-			{
-				visiting_tracker.adjacent(x, y, z).into_iter()
-					.flatten()
-					.for_each(|(x, y, z)| {
-						if grid.voxels.contains(&grid.address(x, y, z)) {
-							faces += 1;
-						} else {
-							visiting_tracker.set(x, y, z);
-						}
-					});
-				visited_tracker.set(x, y ,z);
-			}
+			let pockets = droplet.air_pockets();
+			eprintln!("{} air pockets, with volumes {:?}", pockets.len(), pockets);
 
-			println!("{}", faces);
-		}
+			println!("{}", droplet.exterior_surface_area());
+		},
 	}
 }
\ No newline at end of file
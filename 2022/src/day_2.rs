@@ -5,9 +5,8 @@ use crate::Input;
 #[derive(Debug)]
 pub(crate) enum StrategyGuideParseError {
 	Utf8Error(Utf8Error),
-	UnknownAction,
+	UnknownLetter(char),
 	MalformedRound,
-	UnknownOutcome,
 }
 
 impl From<Utf8Error> for StrategyGuideParseError {
@@ -16,184 +15,274 @@ impl From<Utf8Error> for StrategyGuideParseError {
     }
 }
 
+#[derive(Debug)]
+pub(crate) enum StrategyError {
+	UnknownMove(usize),
+	UnknownColumn(usize),
+}
+
 #[derive(Debug, Clone, Copy)]
-enum Action {
-	Rock,
-	Paper,
-	Scissors,
+enum RoundOutcome {
+	Lose,
+	Draw,
+	Win,
 }
 
-impl Action {
+impl RoundOutcome {
 	fn score(&self) -> usize {
 		match self {
-			Action::Rock => 1,
-			Action::Paper => 2,
-			Action::Scissors => 3,
+			RoundOutcome::Win => 6,
+			RoundOutcome::Draw => 3,
+			RoundOutcome::Lose => 0,
 		}
 	}
 }
 
+// each move beats the half of the other moves just before it and loses to the half just after,
+// so rock-paper-scissors-lizard-Spock is rock, Spock, paper, lizard, scissors
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Game {
+	moves: usize,
+}
 
-impl Action {
-	fn try_from_opponent(s: char) -> Result<Self, StrategyGuideParseError> {
-		match s {
-			'A' => Ok(Action::Rock),
-			'B' => Ok(Action::Paper),
-			'C' => Ok(Action::Scissors),
-			_ => Err(StrategyGuideParseError::UnknownAction)
+pub(crate) const ROCK_PAPER_SCISSORS: Game = Game { moves: 3 };
+
+impl Game {
+	// with an even number of moves some pairs would have no winner
+	pub fn new(moves: usize) -> Option<Self> {
+		(moves % 2 == 1).then_some(Self { moves })
+	}
+
+	fn outcome(&self, you: usize, opponent: usize) -> RoundOutcome {
+		match (you + self.moves - opponent) % self.moves {
+			0 => RoundOutcome::Draw,
+			d if d <= self.moves / 2 => RoundOutcome::Win,
+			_ => RoundOutcome::Lose,
 		}
 	}
 
-	fn try_from_you(s: char) -> Result<Self, StrategyGuideParseError> {
-		match s {
-			'X' => Ok(Action::Rock),
-			'Y' => Ok(Action::Paper),
-			'Z' => Ok(Action::Scissors),
-			_ => Err(StrategyGuideParseError::UnknownAction)
+	fn move_for(&self, outcome: RoundOutcome, opponent: usize) -> usize {
+		match outcome {
+			RoundOutcome::Lose => (opponent + self.moves - 1) % self.moves,
+			RoundOutcome::Draw => opponent,
+			RoundOutcome::Win => (opponent + 1) % self.moves,
 		}
 	}
-}
 
-enum RoundOutcome {
-	Win,
-	Draw,
-	Lose,
+	fn score(&self, you: usize, opponent: usize) -> usize {
+		you + 1 + self.outcome(you, opponent).score()
+	}
 }
 
-impl RoundOutcome {
-	fn score(&self) -> usize {
-		match self {
-			RoundOutcome::Win => 6,
-			RoundOutcome::Draw => 3,
-			RoundOutcome::Lose => 0,
+// the Hungarian algorithm, giving the column assigned to each row of a square cost matrix
+fn cheapest_assignment(costs: &[Vec<i64>]) -> Vec<usize> {
+	let n = costs.len();
+
+	// counted from 1, with row and column 0 standing in for the row being added
+	let mut row_potential = vec![0; n + 1];
+	let mut column_potential = vec![0; n + 1];
+	let mut column_row = vec![0; n + 1];
+	let mut previous = vec![0; n + 1];
+
+	for row in 1..=n {
+		column_row[0] = row;
+		let mut column = 0;
+		let mut slack = vec![i64::MAX; n + 1];
+		let mut visited = vec![false; n + 1];
+
+		// grow a tree of tight edges until it reaches an unassigned column
+		while column_row[column] != 0 {
+			visited[column] = true;
+			let current = column_row[column];
+			let mut delta = i64::MAX;
+			let mut next = 0;
+
+			for j in 1..=n {
+				if visited[j] {
+					continue;
+				}
+
+				let reduced = costs[current - 1][j - 1] - row_potential[current] - column_potential[j];
+				if reduced < slack[j] {
+					slack[j] = reduced;
+					previous[j] = column;
+				}
+				if slack[j] < delta {
+					delta = slack[j];
+					next = j;
+				}
+			}
+
+			for j in 0..=n {
+				if visited[j] {
+					row_potential[column_row[j]] += delta;
+					column_potential[j] -= delta;
+				} else {
+					slack[j] -= delta;
+				}
+			}
+
+			column = next;
 		}
-	}
-}
 
-impl RoundOutcome {
-	fn try_from_char(s: char) -> Result<Self, StrategyGuideParseError> {
-		match s {
-			'X' => Ok(RoundOutcome::Lose),
-			'Y' => Ok(RoundOutcome::Draw),
-			'Z' => Ok(RoundOutcome::Win),
-			_ => Err(StrategyGuideParseError::UnknownOutcome)
+		// then shift the assignments back along the path to it
+		while column != 0 {
+			let before = previous[column];
+			column_row[column] = column_row[before];
+			column = before;
 		}
 	}
 
+	let mut assignment = vec![0; n];
+	for (column, row) in column_row.iter().enumerate().skip(1) {
+		assignment[row - 1] = column - 1;
+	}
+	assignment
 }
 
-#[derive(Debug)]
-pub(crate) struct Round {
-	opponent: Action,
-	you: Action,
+#[derive(Debug, Clone)]
+pub(crate) enum Column {
+	Move(Vec<usize>),
+	// `X` to lose, `Y` to draw and `Z` to win
+	Outcome,
 }
 
-impl Round {
-    fn try_from_action(value: &str) -> Result<Self, StrategyGuideParseError> {
-		let opponent = value.chars().nth(0)
-			.ok_or(StrategyGuideParseError::MalformedRound)
-			.and_then(Action::try_from_opponent)?;
-		let you = value.chars().nth(2)
-			.ok_or(StrategyGuideParseError::MalformedRound)
-			.and_then(Action::try_from_you)?;
-
-		Ok(Self { opponent, you })
-    }
-
-    fn try_from_outcome(value: &str) -> Result<Self, StrategyGuideParseError> {
-		let opponent = value.chars().nth(0)
-			.ok_or(StrategyGuideParseError::MalformedRound)
-			.and_then(Action::try_from_opponent)?;
-		let outcome = value.chars().nth(2)
-			.ok_or(StrategyGuideParseError::MalformedRound)
-			.and_then(RoundOutcome::try_from_char)?;
+impl Column {
+	pub fn in_order(game: Game) -> Self {
+		Self::Move((0..game.moves).collect())
+	}
+}
 
-		Ok(Self::from_outcome(opponent, outcome))
-    }
+#[derive(Debug)]
+pub(crate) struct StrategyGuide {
+	// the letters in each column, counted from `A` and `X` respectively
+	rounds: Vec<(usize, usize)>,
 }
 
-impl Round {
-	fn outcome(&self) -> RoundOutcome {
-		match (self.you, self.opponent) {
-			(Action::Rock, Action::Scissors) => RoundOutcome::Win,
-			(Action::Paper, Action::Rock) => RoundOutcome::Win,
-			(Action::Scissors, Action::Paper) => RoundOutcome::Win,
-
-			(Action::Rock, Action::Rock) => RoundOutcome::Draw,
-			(Action::Paper, Action::Paper) => RoundOutcome::Draw,
-			(Action::Scissors, Action::Scissors) => RoundOutcome::Draw,
-			
-			(Action::Rock, Action::Paper) => RoundOutcome::Lose,
-			(Action::Paper, Action::Scissors) => RoundOutcome::Lose,
-			(Action::Scissors, Action::Rock) => RoundOutcome::Lose,
+impl StrategyGuide {
+	fn parse_round(round: &str) -> Result<(usize, usize), StrategyGuideParseError> {
+		// counted from `first`, wrapping round from `Z` to `A`
+		let index = |c: char, first: char| match c {
+			'A'..='Z' => Ok((c as usize + 26 - first as usize) % 26),
+			_ => Err(StrategyGuideParseError::UnknownLetter(c)),
+		};
+
+		match round.chars().collect::<Vec<_>>()[..] {
+			[opponent, ' ', column] => Ok((index(opponent, 'A')?, index(column, 'X')?)),
+			_ => Err(StrategyGuideParseError::MalformedRound),
 		}
 	}
 
-	fn from_outcome(opponent: Action, outcome: RoundOutcome) -> Self {
-		let you = match (outcome, opponent) {
-			(RoundOutcome::Win, Action::Rock) => Action::Paper,
-			(RoundOutcome::Win, Action::Paper) => Action::Scissors,
-			(RoundOutcome::Win, Action::Scissors) => Action::Rock,
-
-			(RoundOutcome::Draw, Action::Rock) => Action::Rock,
-			(RoundOutcome::Draw, Action::Paper) => Action::Paper,
-			(RoundOutcome::Draw, Action::Scissors) => Action::Scissors,
-			
-			(RoundOutcome::Lose, Action::Rock) => Action::Scissors,
-			(RoundOutcome::Lose, Action::Paper) => Action::Rock,
-			(RoundOutcome::Lose, Action::Scissors) => Action::Paper,
-		};
+	pub(crate) fn score(&self, game: Game, column: &Column) -> Result<usize, StrategyError> {
+		self.rounds.iter()
+			.map(|(opponent, letter)| {
+				if *opponent >= game.moves {
+					return Err(StrategyError::UnknownMove(*opponent));
+				}
 
-		Self { opponent, you }
-	}
+				let you = match column {
+					Column::Move(mapping) => mapping.get(*letter).copied()
+						.filter(|m| *m < game.moves),
+					Column::Outcome => [RoundOutcome::Lose, RoundOutcome::Draw, RoundOutcome::Win]
+						.get(*letter)
+						.map(|outcome| game.move_for(*outcome, *opponent)),
+				};
 
-	fn score(&self) -> usize {
-		self.you.score() + self.outcome().score()
+				you.map(|you| game.score(you, *opponent))
+					.ok_or(StrategyError::UnknownColumn(*letter))
+			})
+			.sum()
 	}
-}
 
-#[derive(Debug)]
-pub(crate) struct ActionStrategyGuide {
-	rounds: Vec<Round>,
+	pub(crate) fn best_mapping(&self, game: Game) -> Result<(Column, usize), StrategyError> {
+		// only how often each pair of letters comes up matters
+		let mut counts = vec![vec![0; game.moves]; game.moves];
+		for (opponent, letter) in self.rounds.iter() {
+			if *opponent >= game.moves {
+				return Err(StrategyError::UnknownMove(*opponent));
+			}
+			if *letter >= game.moves {
+				return Err(StrategyError::UnknownColumn(*letter));
+			}
+			counts[*opponent][*letter] += 1;
+		}
+
+		// the cost of reading each letter as each move, negated to find the highest score
+		let costs = (0..game.moves)
+			.map(|letter| {
+				(0..game.moves)
+					.map(|you| {
+						let total = counts.iter()
+							.enumerate()
+							.map(|(opponent, row)| row[letter] * game.score(you, opponent))
+							.sum::<usize>();
+						-(total as i64)
+					})
+					.collect::<Vec<_>>()
+			})
+			.collect::<Vec<_>>();
+
+		let mapping = cheapest_assignment(&costs);
+		let total = mapping.iter()
+			.enumerate()
+			.map(|(letter, you)| -costs[letter][*you] as usize)
+			.sum();
+
+		Ok((Column::Move(mapping), total))
+	}
 }
 
-impl Input for ActionStrategyGuide {
+impl Input for StrategyGuide {
     type Error = StrategyGuideParseError;
 
     fn parse_str(data: &str) -> Result<Self, Self::Error> {
         data.trim()
 			.split('\n')
-			.map(|round| Round::try_from_action(round.trim()))
+			.map(|round| Self::parse_round(round.trim()))
 			.collect::<Result<Vec<_>, _>>()
 			.map(|rounds| Self { rounds })
     }
 }
 
-impl ActionStrategyGuide {
-	pub(crate) fn score(&self) -> usize {
-		self.rounds.iter().map(Round::score).sum()
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const EXAMPLE: &str = include_str!("../inputs/2/example.txt");
+
+	#[test]
+	fn best_mapping() {
+		let guide = StrategyGuide::parse_str(EXAMPLE).unwrap();
+
+		let (mapping, score) = guide.best_mapping(ROCK_PAPER_SCISSORS).unwrap();
+		assert!(matches!(mapping, Column::Move(ref m) if m == &[2, 1, 0]));
+		assert_eq!(guide.score(ROCK_PAPER_SCISSORS, &mapping).unwrap(), score);
+		assert_eq!(score, 24);
 	}
-}
 
-#[derive(Debug)]
-pub(crate) struct OutcomeStrategyGuide {
-	rounds: Vec<Round>,
-}
+	fn best_by_search(guide: &StrategyGuide, game: Game, mapping: &mut Vec<usize>) -> usize {
+		if mapping.len() == game.moves {
+			return guide.score(game, &Column::Move(mapping.clone())).unwrap();
+		}
 
-impl Input for OutcomeStrategyGuide {
-    type Error = StrategyGuideParseError;
+		let mut best = 0;
+		for you in 0..game.moves {
+			if !mapping.contains(&you) {
+				mapping.push(you);
+				best = best.max(best_by_search(guide, game, mapping));
+				mapping.pop();
+			}
+		}
+		best
+	}
 
-    fn parse_str(data: &str) -> Result<Self, Self::Error> {
-        data.trim()
-			.split('\n')
-			.map(|round| Round::try_from_outcome(round.trim()))
-			.collect::<Result<Vec<_>, _>>()
-			.map(|rounds| Self { rounds })
-    }
-}
+	#[test]
+	fn best_mapping_with_more_moves() {
+		let game = Game::new(5).unwrap();
+		let guide = StrategyGuide::parse_str("A X\nB A\nE Y\nC X\nD Z\nE B\nA A\nB Y").unwrap();
 
-impl OutcomeStrategyGuide {
-	pub(crate) fn score(&self) -> usize {
-		self.rounds.iter().map(Round::score).sum()
+		let (mapping, score) = guide.best_mapping(game).unwrap();
+		assert_eq!(guide.score(game, &mapping).unwrap(), score);
+		assert_eq!(score, best_by_search(&guide, game, &mut vec![]));
 	}
 }
\ No newline at end of file
@@ -1,5 +1,4 @@
 use std::str::Utf8Error;
-use std::collections::HashSet;
 
 use crate::Input;
 
@@ -16,21 +15,56 @@ impl From<Utf8Error> for PackingParseError {
     }
 }
 
-#[derive(Debug)]
-pub(crate) struct Rucksack {
-	items: Vec<u8>,
-}
+// a bit for each priority from 1 to 52
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct ItemSet(u64);
 
-impl Rucksack {
-	fn first_compartment(&self) -> &[u8] {
-		self.items.split_at(self.items.len() / 2).0
+impl ItemSet {
+	fn insert(&mut self, priority: u8) {
+		self.0 |= 1 << priority;
 	}
 
-	fn second_compartment(&self) -> &[u8] {
-		self.items.split_at(self.items.len() / 2).1
+	pub(crate) fn intersection(self, other: Self) -> Self {
+		Self(self.0 & other.0)
 	}
 
-	fn parse_compartment(s: &str) -> Result<Vec<u8>, PackingParseError> {
+	pub(crate) fn union(self, other: Self) -> Self {
+		Self(self.0 | other.0)
+	}
+
+	pub(crate) fn common(sets: impl IntoIterator<Item = Self>) -> Self {
+		sets.into_iter()
+			.reduce(Self::intersection)
+			.unwrap_or_default()
+	}
+
+	fn single(self) -> Result<usize, PackingError> {
+		match self.0.count_ones() {
+			0 => Err(PackingError::NoMatch),
+			1 => Ok(self.0.trailing_zeros() as usize),
+			_ => Err(PackingError::MultipleMatches),
+		}
+	}
+}
+
+impl FromIterator<u8> for ItemSet {
+	fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+		let mut set = Self::default();
+		for priority in iter {
+			set.insert(priority);
+		}
+		set
+	}
+}
+
+#[derive(Debug)]
+pub(crate) struct Rucksack {
+	first_compartment: ItemSet,
+	second_compartment: ItemSet,
+}
+
+impl Rucksack {
+	fn parse_compartment(s: &str) -> Result<ItemSet, PackingParseError> {
 		s.chars()
 			.map(|c| {
 				match c {
@@ -43,20 +77,24 @@ impl Rucksack {
 	}
 
 	fn from_str(s: &str) -> Result<Self, PackingParseError> {
-		if s.len() % 2 == 0 {
+		if s.len().is_multiple_of(2) {
+			let (first, second) = s.split_at(s.len() / 2);
+
 			Ok(Self {
-				items: Self::parse_compartment(s)?,
+				first_compartment: Self::parse_compartment(first)?,
+				second_compartment: Self::parse_compartment(second)?,
 			})
 		} else {
 			Err(PackingParseError::OddPackageCount)
 		}
 	}
 
-	pub(crate) fn incorrect_items(&self) -> Vec<u8> {
-		let a = HashSet::<u8>::from_iter(self.first_compartment().iter().copied());
-		let b = HashSet::<u8>::from_iter(self.second_compartment().iter().copied());
+	pub(crate) fn items(&self) -> ItemSet {
+		self.first_compartment.union(self.second_compartment)
+	}
 
-		a.intersection(&b).copied().collect()
+	pub(crate) fn incorrect_items(&self) -> ItemSet {
+		self.first_compartment.intersection(self.second_compartment)
 	}
 }
 
@@ -80,38 +118,42 @@ impl Input for Packing {
 pub(crate) enum PackingError {
 	NoMatch,
 	MultipleMatches,
+	InvalidGroupSize,
+	IncompleteGroup {
+		rucksacks: usize,
+		group_size: usize,
+	},
 }
 
 impl Packing {
 	pub(crate) fn wrong_item_priority_sum(&self) -> Result<usize, PackingError> {
 		self.rucksacks.iter()
-			.map(|sack| match &sack.incorrect_items()[..] {
-				[] => Err(PackingError::NoMatch),
-				[item] => Ok(*item as usize),
-				_ => Err(PackingError::MultipleMatches)
-			})
-			.collect::<Result<Vec<_>, _>>()
-			.map(|list| list.iter().sum())
+			.map(|sack| sack.incorrect_items().single())
+			.sum()
 	}
 
-	pub(crate) fn badges_priority_sum(&self) -> Result<usize, PackingError> {
-		self.rucksacks.
-			chunks(3)
-			.map(|group| {
-				group.iter()
-					.map(|rucksack| {
-						HashSet::<u8>::from_iter(rucksack.items.iter().copied())
-					})
-					.reduce(|a, b| a.intersection(&b).copied().collect())
-					.unwrap()
-			}) 
-			.map(|badges| badges.iter().copied().collect::<Vec<_>>())
-			.map(|badges| match &badges[..] {
-				[] => Err(PackingError::NoMatch),
-				[item] => Ok(*item as usize),
-				_ => Err(PackingError::MultipleMatches)
-			})
-			.collect::<Result<Vec<_>, _>>()
-			.map(|list| list.iter().sum())
+	pub(crate) fn group_items(&self, group_size: usize) -> Result<Vec<ItemSet>, PackingError> {
+		if group_size == 0 {
+			return Err(PackingError::InvalidGroupSize);
+		}
+
+		if !self.rucksacks.len().is_multiple_of(group_size) {
+			return Err(PackingError::IncompleteGroup {
+				rucksacks: self.rucksacks.len(),
+				group_size,
+			});
+		}
+
+		Ok(self.rucksacks
+			.chunks(group_size)
+			.map(|group| ItemSet::common(group.iter().map(Rucksack::items)))
+			.collect())
+	}
+
+	pub(crate) fn badges_priority_sum(&self, group_size: usize) -> Result<usize, PackingError> {
+		self.group_items(group_size)?
+			.into_iter()
+			.map(ItemSet::single)
+			.sum()
 	}
 }
\ No newline at end of file
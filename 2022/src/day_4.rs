@@ -1,39 +1,50 @@
-use std::ops::RangeInclusive;
+use crate::{Part, interval::{Interval, IntervalParseError}};
 
-use crate::Part;
+type Assignment = Interval<u64>;
 
-fn parse_range(range: &str) -> RangeInclusive<usize> {
-	let (start, end) = range.split_once('-').unwrap();
-	let start = start.parse::<usize>().unwrap();
-	let end = end.parse::<usize>().unwrap();
-	start..=end
+#[derive(Debug)]
+pub(crate) enum PairError {
+	MissingComma,
+	Assignment(IntervalParseError),
 }
 
-pub(crate) fn solve(data: &[u8], part: Part) {
-	let pairs = std::str::from_utf8(data).unwrap()
-		.split('\n')
-		.map(|pair| {
-			let (a, b) = pair.split_once(',').unwrap();
-			(parse_range(a), parse_range(b))
-		})
-		.collect::<Vec<(RangeInclusive<usize>, RangeInclusive<usize>)>>();
-	
-
-	match part {
-		Part::A => {
-			let overlapping = pairs.iter().filter(|(a, b)| {
-				b.clone().step_by(1).all(|v| a.contains(&v)) ||
-				a.clone().step_by(1).all(|v| b.contains(&v))
-			});
-
-			println!("{:?}", overlapping.count())
-		},
-		Part::B => {
-			let overlapping = pairs.iter().filter(|(a, b)| {
-				b.clone().step_by(1).any(|v| a.contains(&v))
-			});
-
-			println!("{:?}", overlapping.count())
-		},
+impl From<IntervalParseError> for PairError {
+	fn from(error: IntervalParseError) -> Self {
+		Self::Assignment(error)
 	}
+}
+
+#[derive(Debug)]
+pub(crate) struct AssignmentError {
+	// counted from 1
+	line: usize,
+	error: PairError,
+}
+
+fn parse_pair(pair: &str) -> Result<(Assignment, Assignment), PairError> {
+	let (a, b) = pair.split_once(',').ok_or(PairError::MissingComma)?;
+	Ok((a.parse()?, b.parse()?))
+}
+
+pub(crate) fn parse_pairs(input: &str) -> Result<Vec<(Assignment, Assignment)>, AssignmentError> {
+	input.split('\n')
+		.enumerate()
+		.map(|(index, pair)| parse_pair(pair).map_err(|error| AssignmentError { line: index + 1, error }))
+		.collect()
+}
+
+pub(crate) fn solve(data: &[u8], part: Part) {
+	let pairs = parse_pairs(std::str::from_utf8(data).unwrap())
+		.expect("input parse error");
+
+	let count = match part {
+		Part::A => pairs.iter()
+			.filter(|(a, b)| a.contains_interval(b) || b.contains_interval(a))
+			.count(),
+		Part::B => pairs.iter()
+			.filter(|(a, b)| a.overlaps(b))
+			.count(),
+	};
+
+	println!("{}", count);
 }
\ No newline at end of file
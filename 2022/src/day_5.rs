@@ -1,74 +1,256 @@
+use std::collections::HashMap;
+
 use nom::{sequence::{preceded, tuple}, bytes::complete::tag};
 
 use crate::{Part, take_positive_number};
 
-pub(crate) fn solve(data: &[u8], part: Part) {
-	let (arrangement, instructions) = std::str::from_utf8(&data).unwrap()
-		.split_once("\n\n").unwrap();
+#[derive(Debug)]
+pub(crate) enum StacksParseError {
+	MissingInstructions,
+	MissingLabels,
+	BadLabel(String),
+	BadInstruction(String),
+}
 
-		struct Instruction {
-		source: usize,
-		destination: usize,
-		count: usize,
-	}
+#[derive(Debug)]
+pub(crate) enum MoveError {
+	UnknownStack(usize),
+	NotEnoughCrates {
+		stack: usize,
+		requested: usize,
+		available: usize,
+	},
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Crane {
+	// moves crates one at a time, reversing their order
+	CrateMover9000,
+	CrateMover9001,
+}
+
+#[derive(Debug)]
+pub(crate) struct Instruction {
+	source: usize,
+	destination: usize,
+	count: usize,
+}
 
-	let instructions = instructions.split('\n').map(|i| {
-		let (_, (count, source, destination)) = tuple((
+impl Instruction {
+	fn parse(input: &str) -> Result<Self, StacksParseError> {
+		match tuple((
 			preceded(tag("move "), take_positive_number),
 			preceded(tag(" from "), take_positive_number),
 			preceded(tag(" to "), take_positive_number),
-		))(i).unwrap();
-
-		Instruction { source, destination, count }
-	});
-
-	let mut arrangement = arrangement.split('\n').rev();
-	let labels = arrangement.next().unwrap()
-		.split_whitespace()
-		.filter(|n| !n.is_empty())
-		.map(|n| n.parse::<usize>().unwrap())
-		.collect::<Vec<usize>>();
-
-	let mut stacks = (0..labels.len())
-		.map(|_| std::collections::VecDeque::<char>::new())
-		.collect::<Vec<_>>();
-
-	for line in arrangement {
-		for stack in 0..labels.len() {
-			let pos = stack * 4 + 1;
-			let c = line.as_bytes()[pos] as char;
-			if c != ' ' {
-				stacks[stack].push_back(c)
-			}
+		))(input) {
+			Ok(("", (count, source, destination))) => Ok(Instruction { source, destination, count }),
+			_ => Err(StacksParseError::BadInstruction(input.to_owned())),
 		}
 	}
+}
 
+#[derive(Debug)]
+pub(crate) struct Stacks {
+	labels: Vec<usize>,
+	indices: HashMap<usize, usize>,
+	// each from the bottom up
+	stacks: Vec<Vec<char>>,
+}
 
-	for Instruction { source, destination: dest, count }  in instructions {
-		let source = labels.iter().position(|i| *i == source).unwrap();
-		let dest = labels.iter().position(|i| *i == dest).unwrap();
+impl Stacks {
+	pub fn parse(drawing: &str) -> Result<Self, StacksParseError> {
+		let mut lines = drawing.lines().rev();
+		let label_line = lines.next().ok_or(StacksParseError::MissingLabels)?;
 
-		match part {
-			Part::A => {
-				for _ in 0..count {
-					let value = stacks[source].pop_back().unwrap();
-					stacks[dest].push_back(value);
-				}
-			},
-			Part::B => {
-				let mut buffer = std::collections::VecDeque::new();
-				for _ in 0..count {
-					let value = stacks[source].pop_back().unwrap();
-					buffer.push_front(value);
+		// each label sits in the same column as the crates above it
+		let columns = label_line.char_indices()
+			.filter(|(_, c)| !c.is_whitespace())
+			.map(|(i, _)| i)
+			.filter(|i| *i == 0 || label_line.as_bytes()[i - 1] == b' ')
+			.collect::<Vec<_>>();
+
+		let labels = label_line.split_whitespace()
+			.map(|l| l.parse::<usize>().map_err(|_| StacksParseError::BadLabel(l.to_owned())))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		if labels.is_empty() {
+			return Err(StacksParseError::MissingLabels);
+		}
+
+		let mut stacks = vec![vec![]; labels.len()];
+
+		for line in lines {
+			for (stack, column) in stacks.iter_mut().zip(columns.iter()) {
+				// lines are often trimmed, so short ones just have nothing in the later stacks
+				match line.as_bytes().get(*column) {
+					Some(b' ') | None => (),
+					Some(c) => stack.push(*c as char),
 				}
-				stacks[dest].append(&mut buffer);
-			},
+			}
 		}
+
+		let indices = labels.iter()
+			.enumerate()
+			.map(|(i, label)| (*label, i))
+			.collect();
+
+		Ok(Self { labels, indices, stacks })
+	}
+
+	fn index(&self, label: usize) -> Result<usize, MoveError> {
+		self.indices.get(&label).copied().ok_or(MoveError::UnknownStack(label))
 	}
 
-	
-	let message = stacks.iter()
-		.map(|stack| stack.back().unwrap_or(&' '))
-		.collect::<String>();
-	println!("{}", message);
+	pub fn apply(&mut self, instruction: &Instruction, crane: Crane) -> Result<(), MoveError> {
+		let source = self.index(instruction.source)?;
+		let destination = self.index(instruction.destination)?;
+		let count = instruction.count;
+
+		let available = self.stacks[source].len();
+		if available < count {
+			return Err(MoveError::NotEnoughCrates {
+				stack: instruction.source,
+				requested: count,
+				available,
+			});
+		}
+
+		if source == destination {
+			if let Crane::CrateMover9000 = crane {
+				self.stacks[source][available - count..].reverse();
+			}
+			return Ok(());
+		}
+
+		let (from, to) = if source < destination {
+			let (low, high) = self.stacks.split_at_mut(destination);
+			(&mut low[source], &mut high[0])
+		} else {
+			let (low, high) = self.stacks.split_at_mut(source);
+			(&mut high[0], &mut low[destination])
+		};
+
+		let moved = from.drain(available - count..);
+
+		match crane {
+			Crane::CrateMover9000 => to.extend(moved.rev()),
+			Crane::CrateMover9001 => to.extend(moved),
+		}
+
+		Ok(())
+	}
+
+	pub fn tops(&self) -> String {
+		self.stacks.iter()
+			.map(|stack| stack.last().unwrap_or(&' '))
+			.collect()
+	}
+}
+
+impl std::fmt::Display for Stacks {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let height = self.stacks.iter().map(Vec::len).max().unwrap_or(0);
+
+		for level in (0..height).rev() {
+			let line = self.stacks.iter()
+				.map(|stack| match stack.get(level) {
+					Some(c) => format!("[{}]", c),
+					None => "   ".to_owned(),
+				})
+				.collect::<Vec<_>>()
+				.join(" ");
+			writeln!(f, "{}", line)?;
+		}
+
+		let labels = self.labels.iter()
+			.map(|label| format!(" {} ", label))
+			.collect::<Vec<_>>()
+			.join(" ");
+		writeln!(f, "{}", labels)
+	}
+}
+
+pub(crate) fn solve(data: &[u8], part: Part) {
+	let (drawing, instructions) = std::str::from_utf8(data).unwrap()
+		.split_once("\n\n")
+		.ok_or(StacksParseError::MissingInstructions)
+		.expect("input parse error");
+
+	let mut stacks = Stacks::parse(drawing).expect("input parse error");
+
+	let instructions = instructions.lines()
+		.map(Instruction::parse)
+		.collect::<Result<Vec<_>, _>>()
+		.expect("input parse error");
+
+	let crane = match part {
+		Part::A => Crane::CrateMover9000,
+		Part::B => Crane::CrateMover9001,
+	};
+
+	for instruction in instructions.iter() {
+		stacks.apply(instruction, crane).expect("invalid move");
+	}
+
+	println!("{}", stacks.tops());
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const EXAMPLE: &str = include_str!("../inputs/5/example.txt");
+
+	fn example() -> (Stacks, Vec<Instruction>) {
+		let (drawing, instructions) = EXAMPLE.split_once("\n\n").unwrap();
+
+		let stacks = Stacks::parse(drawing).unwrap();
+		let instructions = instructions.lines()
+			.map(|l| Instruction::parse(l).unwrap())
+			.collect();
+
+		(stacks, instructions)
+	}
+
+	#[test]
+	fn render() {
+		let (drawing, _) = EXAMPLE.split_once("\n\n").unwrap();
+		let (stacks, _) = example();
+
+		assert_eq!(stacks.to_string(), drawing.to_owned() + "\n");
+	}
+
+	#[test]
+	fn trimmed_drawing() {
+		let stacks = Stacks::parse("    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3").unwrap();
+
+		assert_eq!(stacks.tops(), "NDP");
+	}
+
+	#[test]
+	fn cranes() {
+		for (crane, tops) in [(Crane::CrateMover9000, "CMZ"), (Crane::CrateMover9001, "MCD")] {
+			let (mut stacks, instructions) = example();
+
+			for instruction in instructions.iter() {
+				stacks.apply(instruction, crane).unwrap();
+			}
+
+			assert_eq!(stacks.tops(), tops);
+		}
+	}
+
+	#[test]
+	fn bad_moves() {
+		let (mut stacks, _) = example();
+
+		let too_many = Instruction::parse("move 4 from 1 to 2").unwrap();
+		assert!(matches!(
+			stacks.apply(&too_many, Crane::CrateMover9000),
+			Err(MoveError::NotEnoughCrates { stack: 1, requested: 4, available: 2 })
+		));
+
+		let unknown = Instruction::parse("move 1 from 4 to 2").unwrap();
+		assert!(matches!(stacks.apply(&unknown, Crane::CrateMover9000), Err(MoveError::UnknownStack(4))));
+	}
 }
\ No newline at end of file
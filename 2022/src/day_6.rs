@@ -1,22 +1,112 @@
+use std::collections::VecDeque;
+use std::io::Read;
+
 use crate::Part;
 
+#[derive(Debug)]
+pub(crate) enum MarkerError {
+	InvalidSize,
+}
+
+#[derive(Debug)]
+struct DistinctWindow {
+	size: usize,
+	window: VecDeque<u8>,
+	counts: [usize; 256],
+	distinct: usize,
+}
+
+impl DistinctWindow {
+	// a marker needs at least one byte
+	fn new(size: usize) -> Option<Self> {
+		(size > 0).then(|| Self {
+			size,
+			window: VecDeque::with_capacity(size),
+			counts: [0; 256],
+			distinct: 0,
+		})
+	}
+
+	fn push(&mut self, byte: u8) {
+		if self.window.len() == self.size {
+			let old = self.window.pop_front().unwrap();
+			self.counts[old as usize] -= 1;
+			if self.counts[old as usize] == 0 {
+				self.distinct -= 1;
+			}
+		}
+
+		self.window.push_back(byte);
+		self.counts[byte as usize] += 1;
+		if self.counts[byte as usize] == 1 {
+			self.distinct += 1;
+		}
+	}
+
+	fn is_marker(&self) -> bool {
+		self.window.len() == self.size && self.distinct == self.size
+	}
+}
+
+// positions are counted in bytes read, so are just after the marker
+pub(crate) fn markers<R: Read>(reader: R, size: usize) -> Result<impl Iterator<Item = std::io::Result<usize>>, MarkerError> {
+	let mut window = DistinctWindow::new(size).ok_or(MarkerError::InvalidSize)?;
+
+	Ok(std::io::BufReader::new(reader)
+		.bytes()
+		.enumerate()
+		.filter_map(move |(index, byte)| match byte {
+			Ok(byte) => {
+				window.push(byte);
+				window.is_marker().then_some(Ok(index + 1))
+			},
+			Err(e) => Some(Err(e)),
+		}))
+}
+
 pub(crate) fn solve(data: &[u8], part: Part) {
 	let size = match part {
 		Part::A => 4,
 		Part::B => 14,
 	};
 
-	let index = data.windows(size)
-		.position(|window| {
-			let mut bitset: usize = 0;
-			for byte in window {
-				let byte_index = byte - b'a';
-				bitset |= 1 << byte_index;
-			}
+	// the line ending isn't part of the datastream
+	let position = markers(data.trim_ascii_end(), size)
+		.expect("invalid marker size")
+		.next()
+		.expect("no marker in the datastream")
+		.expect("read error");
 
-			(bitset.count_ones() as usize) == size
-		})
-		.unwrap();
+	println!("{}", position);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn all_markers(stream: &[u8], size: usize) -> Vec<usize> {
+		markers(stream, size).unwrap().collect::<Result<_, _>>().unwrap()
+	}
+
+	#[test]
+	fn first_marker() {
+		assert_eq!(all_markers(b"mjqjpqmgbljsphdztnvjfqwrcgsmlb", 4)[0], 7);
+		assert_eq!(all_markers(b"mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14)[0], 19);
+	}
+
+	#[test]
+	fn every_marker() {
+		assert_eq!(all_markers(b"aabcab", 3), [4, 5, 6]);
+		assert_eq!(all_markers(b"aaaa", 2), []);
+	}
+
+	#[test]
+	fn any_byte() {
+		assert_eq!(all_markers(b"\n\n\xff\x00A", 3), [4, 5]);
+	}
 
-	println!("{}", index + size);
+	#[test]
+	fn empty_window() {
+		assert!(matches!(markers(b"abc".as_slice(), 0), Err(MarkerError::InvalidSize)));
+	}
 }
\ No newline at end of file
@@ -1,56 +1,6 @@
 use std::collections::HashMap;
 
-#[derive(Debug)]
-struct Node<T> {
-	data: T,
-	children: Vec<usize>,
-	parent: Option<usize>,
-}
-
-#[derive(Debug, Default)]
-pub struct Tree<T> {
-	nodes: Vec<Node<T>>
-}
-
-impl<T> Tree<T> {
-	fn push_root(&mut self, data: T) -> usize {
-		self.nodes.push(Node {
-			data,
-			parent: None,
-			children: vec![],
-		});
-		self.nodes.len() - 1
-	}
-
-	fn node(&self, node_id: usize) -> Option<&Node<T>> {
-		self.nodes.get(node_id)
-	}
-
-	fn node_mut(&mut self, node_id: usize) -> Option<&mut Node<T>> {
-		self.nodes.get_mut(node_id)
-	}
-
-	fn children(&self, node_id: usize) -> Option<&Vec<usize>> {
-		self.node(node_id).map(|n| &n.children)
-	}
-
-	fn push_child(&mut self, data: T, parent: usize) -> usize {
-		self.nodes.push(Node {
-			data,
-			parent: Some(parent),
-			children: vec![],
-		});
-		let id = self.nodes.len() - 1;
-
-		self.nodes[parent].children.push(id);
-
-		id
-	}
-
-	fn len(&self) -> usize {
-		self.nodes.len()
-	}
-}
+use crate::tree::Tree;
 
 #[derive(Debug, Default)]
 pub struct Directory {
@@ -67,62 +17,84 @@ impl Directory {
 #[derive(Debug)]
 pub enum CommandError {
 	MissingCommand,
+	UnknownCommand(String),
 	MissingArgument,
-	NoDirectory(String),
-	BadList,
+	UnexpectedOutput(String),
+	BadList(String),
 	BadSize(String),
 }
 
-impl Directory {
-	pub fn create_from_str(input: &str) -> Result<Tree<Self>, CommandError> {
-		let commands = input.split("$ ")
-			.skip(1)
-			.map(str::trim_end);
+#[derive(Debug)]
+pub struct SessionError {
+	// counted from 1
+	line: usize,
+	error: CommandError,
+}
 
+impl Directory {
+	pub fn create_from_str(input: &str) -> Result<Tree<Self>, SessionError> {
 		let mut tree = Tree::<Directory>::default();
 		let root = tree.push_root(Directory::new("/".to_owned()));
 		let mut current_directory = root;
-	
-		for execution in commands {
-			let mut lines = execution.split('\n');
-			let command = lines.next().unwrap();
-
-			let (command, arguments) = command.split_once(' ').unwrap_or((command, ""));
-
-			match command {
-				"cd" => {
-					if arguments.is_empty() {
-						return Err(CommandError::MissingArgument);
-					}
-
-					if arguments == "/" {
-						current_directory = root;
-					} else if arguments == ".." {
-						current_directory = tree.node(current_directory).unwrap().parent.unwrap();
-					} else {
-						current_directory = tree.children(current_directory).unwrap()
-							.iter().copied()
-							.find(|c| tree.node(*c).unwrap().data.name == arguments)
-							.ok_or_else(|| CommandError::NoDirectory(arguments.to_owned()))?;
-					}
-				},
-				"ls" => {
-					for entry in lines {
-						let (prefix, name) = entry.split_once(' ').ok_or(CommandError::BadList)?;
-						if prefix == "dir" {
-							tree.push_child(Directory::new(name.to_owned()), current_directory);
-						} else {
-							let size = prefix.parse::<usize>()
-								.map_err(|_| CommandError::BadSize(prefix.to_owned()))?;
-
-							tree.node_mut(current_directory).unwrap()
-								.data
-								.files
-								.insert(name.to_owned(), size);
+		let mut listing = false;
+
+		for (index, line) in input.lines().enumerate() {
+			let fail = |error| SessionError { line: index + 1, error };
+
+			let line = line.trim_end();
+			if line.is_empty() {
+				continue;
+			}
+
+			if let Some(command) = line.strip_prefix('$') {
+				let command = command.trim();
+				let (command, arguments) = command.split_once(' ')
+					.map(|(c, a)| (c, a.trim()))
+					.unwrap_or((command, ""));
+
+				listing = false;
+
+				match command {
+					"cd" => {
+						if arguments.is_empty() {
+							return Err(fail(CommandError::MissingArgument));
+						}
+
+						if arguments.starts_with('/') {
+							current_directory = root;
 						}
-					}
-				},
-				_ => return Err(CommandError::MissingCommand),
+
+						for name in arguments.split('/') {
+							current_directory = match name {
+								"" | "." => current_directory,
+								// like a shell, going up from the root stays there
+								".." => tree.parent(current_directory).unwrap_or(root),
+								// directories don't need to have been listed to be entered
+								name => tree.subdirectory(current_directory, name),
+							};
+						}
+					},
+					"ls" => listing = true,
+					"" => return Err(fail(CommandError::MissingCommand)),
+					command => return Err(fail(CommandError::UnknownCommand(command.to_owned()))),
+				}
+			} else if listing {
+				let (prefix, name) = line.split_once(' ')
+					.ok_or_else(|| fail(CommandError::BadList(line.to_owned())))?;
+
+				if prefix == "dir" {
+					// listing the same directory again shouldn't duplicate it
+					tree.subdirectory(current_directory, name);
+				} else {
+					let size = prefix.parse::<usize>()
+						.map_err(|_| fail(CommandError::BadSize(prefix.to_owned())))?;
+
+					tree.get_mut(current_directory).unwrap()
+						.files
+						.insert(name.to_owned(), size);
+				}
+			} else {
+				return Err(fail(CommandError::UnexpectedOutput(line.to_owned())));
 			}
 		}
 
@@ -132,22 +104,73 @@ impl Directory {
 	fn size(&self) -> usize {
 		self.files.values().sum()
 	}
+}
+
+impl Tree<Directory> {
+	// creates the subdirectory if it doesn't exist yet
+	fn subdirectory(&mut self, dir: usize, name: &str) -> usize {
+		let existing = self.children(dir).unwrap()
+			.iter().copied()
+			.find(|c| self.get(*c).unwrap().name == name);
+
+		existing.unwrap_or_else(|| self.push_child(Directory::new(name.to_owned()), dir))
+	}
+
+	// indexed by node id
+	pub fn sizes(&self) -> Vec<usize> {
+		self.fold(0, |dir, children| dir.size() + children.into_iter().sum::<usize>())
+			.expect("a session always has a root directory")
+			.into_iter()
+			.map(Option::unwrap)
+			.collect()
+	}
+
+	pub fn find(&self, path: &str) -> Option<usize> {
+		let mut names = path.strip_prefix('/')?
+			.split('/')
+			.filter(|n| !n.is_empty());
+
+		names.try_fold(0, |dir, name| {
+			self.children(dir)?
+				.iter().copied()
+				.find(|c| self.get(*c).unwrap().name == name)
+		})
+	}
+
+	pub fn absolute_path(&self, dir: usize) -> Option<String> {
+		let names = self.path(dir)?
+			.into_iter()
+			.skip(1)
+			.map(|d| format!("/{}", self.get(d).unwrap().name))
+			.collect::<String>();
+
+		Some(if names.is_empty() { "/".to_owned() } else { names })
+	}
+
+	pub fn files(&self) -> impl Iterator<Item = (String, usize)> + '_ {
+		self.pre_order(0).into_iter().flatten().flat_map(move |dir| {
+			// avoid doubling up the slash of the root directory
+			let prefix = if dir == 0 { String::new() } else { self.absolute_path(dir).unwrap() };
+
+			self.get(dir).unwrap().files.iter().map(move |(name, size)| {
+				(format!("{}/{}", prefix, name), *size)
+			})
+		})
+	}
 
-	pub fn recursive_size(tree: &Tree<Self>, dir: usize) -> usize {
-		let children_size: usize = tree.children(dir).unwrap()
-			.iter()
-			.map(|c| Directory::recursive_size(tree, *c))
-			.sum();
+	pub fn du(&self) -> String {
+		let sizes = self.sizes();
 
-		return children_size + tree.node(dir).unwrap().data.size()
+		self.post_order(0).into_iter().flatten()
+			.map(|dir| format!("{}\t{}\n", sizes[dir], self.absolute_path(dir).unwrap()))
+			.collect()
 	}
 }
 
 pub fn solve_a(tree: &Tree<Directory>) -> usize {
 	const MAX_SIZE: usize = 100_000;
 
-	(0..tree.len()).into_iter()
-		.map(|i| Directory::recursive_size(tree, i))
+	tree.sizes().into_iter()
 		.filter(|s| *s <= MAX_SIZE)
 		.sum()
 }
@@ -156,14 +179,61 @@ pub fn solve_b(tree: &Tree<Directory>) -> usize {
 	const TOTAL: usize = 70_000_000;
 	const REQUIRED: usize = 30_000_000;
 
-	let used = Directory::recursive_size(tree, 0);
+	let sizes = tree.sizes();
+	let used = sizes[0];
 
 	let free = TOTAL - used;
 	let required_delete = REQUIRED - free;
 
-	(0..tree.len()).into_iter()
-		.map(|i| Directory::recursive_size(tree, i))
+	sizes.into_iter()
 		.filter(|s| *s >= required_delete)
 		.reduce(usize::min)
 		.unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const EXAMPLE: &str = include_str!("../inputs/7/example.txt");
+
+	#[test]
+	fn find() {
+		let tree = Directory::create_from_str(EXAMPLE).unwrap();
+
+		assert_eq!(tree.find("/"), Some(0));
+		assert_eq!(tree.find("/a/e"), Some(3));
+		assert_eq!(tree.find("/a/e/"), Some(3));
+		assert_eq!(tree.find("/a/x"), None);
+		assert_eq!(tree.find("a"), None);
+	}
+
+	#[test]
+	fn absolute_path() {
+		let tree = Directory::create_from_str(EXAMPLE).unwrap();
+
+		assert_eq!(tree.absolute_path(0).as_deref(), Some("/"));
+		assert_eq!(tree.absolute_path(3).as_deref(), Some("/a/e"));
+		assert_eq!(tree.absolute_path(10), None);
+	}
+
+	#[test]
+	fn files() {
+		let tree = Directory::create_from_str(EXAMPLE).unwrap();
+
+		let mut files = tree.files().collect::<Vec<_>>();
+		files.sort();
+
+		assert_eq!(files.len(), 10);
+		assert_eq!(files[0], ("/a/e/i".to_owned(), 584));
+		assert!(files.contains(&("/b.txt".to_owned(), 14848514)));
+		assert!(files.contains(&("/d/k".to_owned(), 7214296)));
+	}
+
+	#[test]
+	fn du() {
+		let tree = Directory::create_from_str(EXAMPLE).unwrap();
+
+		assert_eq!(tree.du(), "584\t/a/e\n94853\t/a\n24933642\t/d\n48381165\t/\n");
+	}
 }
\ No newline at end of file
@@ -1,119 +1,104 @@
 use crate::Part;
 
-pub(crate) fn solve(data: &[u8], part: Part) {	
-	let grid = std::str::from_utf8(data).unwrap()
-		.split('\n')
-		.map(|line| {
-			line.chars()
-				.map(|c| {
-					assert!(c.is_ascii_digit());
-					(c as u8 - b'0') as i8
-				})
-				.collect::<Vec<_>>()
-		})
-		.collect::<Vec<_>>();
-
-	match part {
-		Part::A => {
-			let mut visiblity = grid.iter()
-				.map(|c| vec![false; c.len()])
-				.collect::<Vec<_>>();
-
-			// horizontal
-
-			for (y, line) in grid.iter().enumerate() {
-				let mut best = -1;
-				for (x, value) in line.iter().enumerate() {
-					if *value > best {
-						best = *value;
-						visiblity[y][x] = true;
-					}
-				}
+struct Forest {
+	width: usize,
+	height: usize,
+	trees: Vec<u8>,
+}
+
+// indexed the same as the forest
+struct Survey {
+	visible: Vec<bool>,
+	scenic_scores: Vec<usize>,
+}
+
+impl Forest {
+	fn parse(input: &str) -> Self {
+		let rows = input.split('\n')
+			.map(|line| {
+				line.chars()
+					.map(|c| {
+						assert!(c.is_ascii_digit());
+						c as u8 - b'0'
+					})
+					.collect::<Vec<_>>()
+			})
+			.collect::<Vec<_>>();
+
+		let height = rows.len();
+		let width = rows[0].len();
+		assert!(rows.iter().all(|r| r.len() == width), "forest must be rectangular");
+
+		Self { width, height, trees: rows.concat() }
+	}
+
+	fn survey(&self) -> Survey {
+		let mut survey = Survey {
+			visible: vec![false; self.trees.len()],
+			scenic_scores: vec![1; self.trees.len()],
+		};
+
+		let mut stack = Vec::new();
+
+		for y in 0..self.height {
+			let row = y * self.width..(y + 1) * self.width;
+			self.sweep(row.clone(), &mut survey, &mut stack);
+			self.sweep(row.rev(), &mut survey, &mut stack);
+		}
+
+		for x in 0..self.width {
+			let column = (x..self.trees.len()).step_by(self.width);
+			self.sweep(column.clone(), &mut survey, &mut stack);
+			self.sweep(column.rev(), &mut survey, &mut stack);
+		}
+
+		survey
+	}
+
+	// marks the trees visible from the start of the line,
+	// and scores how far each can see back towards it
+	fn sweep(
+		&self,
+		line: impl Iterator<Item = usize>,
+		survey: &mut Survey,
+		stack: &mut Vec<(usize, u8)>,
+	) {
+		// distances and heights of earlier trees not yet hidden behind a taller one,
+		// so the heights never increase going up the stack
+		stack.clear();
+
+		for (distance, index) in line.enumerate() {
+			let height = self.trees[index];
+
+			while stack.last().is_some_and(|(_, h)| *h < height) {
+				stack.pop();
 			}
 
-			for (y, line) in grid.iter().enumerate() {
-				let mut best = -1;
-				for (x, value) in line.iter().enumerate().rev() {
-					if *value > best {
-						best = *value;
-						visiblity[y][x] = true;
-					}
-				}
-			}
-
-			// vertical
-			let mut bests = vec![-1; grid[0].len()];
-
-			for (y, line) in grid.iter().enumerate() {
-				for (x, value) in line.iter().enumerate() {
-					if *value > bests[x] {
-						bests[x] = *value;
-						visiblity[y][x] = true;
-					}
-				}
-			}
+			let view = match stack.last() {
+				Some((blocker, _)) => distance - blocker,
+				None => {
+					// nothing is as tall, so this tree can be seen from the edge
+					survey.visible[index] = true;
+					distance
+				},
+			};
+			survey.scenic_scores[index] *= view;
+
+			stack.push((distance, height));
+		}
+	}
+}
+
+pub(crate) fn solve(data: &[u8], part: Part) {
+	let forest = Forest::parse(std::str::from_utf8(data).unwrap());
+	let survey = forest.survey();
 
-			let mut bests = vec![-1; grid[0].len()];
-
-			for (y, line) in grid.iter().enumerate().rev() {
-				for (x, value) in line.iter().enumerate() {
-					if *value > bests[x] {
-						bests[x] = *value;
-						visiblity[y][x] = true;
-					}
-				}
-			}
-
-			println!("{:?}", visiblity.iter().flatten().filter(|a| **a).count());
+	match part {
+		Part::A => {
+			println!("{:?}", survey.visible.iter().filter(|a| **a).count());
 		},
 		Part::B => {
-			let mut scenic_score = grid.iter()
-				.map(|c| vec![0; c.len()])
-				.collect::<Vec<_>>();
-
-			let height = grid.len();
-			let width = grid[0].len();
-
-			for (y, row) in grid.iter().enumerate().skip(1).take(height - 2) {
-				for (x, value) in row.iter().enumerate().skip(1).take(width - 2) {
-					let value = *value;
-
-					let mut left_score = 0;
-					for nx in (0..x).rev() {
-						left_score += 1;
-						if row[nx] >= value {
-							break;
-						}
-					}
-
-					let mut right_score = 0;
-					for nx in (x..width).skip(1) {
-						right_score += 1;
-						if row[nx] >= value {
-							break;
-						}
-					}
-
-					let mut up_score = 0;
-					for ny in (0..y).rev() {
-						up_score += 1;
-						if grid[ny][x] >= value {
-							break;
-						}
-					}
-
-					let mut down_score = 0;
-					for ny in (y..height).skip(1) {
-						down_score += 1;
-						if grid[ny][x] >= value {
-							break;
-						}
-					}
-
-					scenic_score[y][x] = left_score * up_score * right_score * down_score;
-				}
-			}
-			println!("{:?}", scenic_score.iter().flatten().max().unwrap());
+			println!("{:?}", survey.scenic_scores.iter().max().unwrap());
 		},
 	};
 }
\ No newline at end of file
@@ -1,81 +1,209 @@
 use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
 
 use crate::Part;
 
-pub(crate) fn solve(data: &[u8], part: Part) {
-	let input = std::str::from_utf8(data).expect("input parse error");
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Direction {
+	Right,
+	Left,
+	Down,
+	Up,
+}
+
+impl TryFrom<char> for Direction {
+	type Error = ();
 
-	enum Direction {
-		Right,
-		Left,
-		Down,
-		Up,
+	fn try_from(c: char) -> Result<Self, Self::Error> {
+		match c {
+			'R' => Ok(Self::Right),
+			'L' => Ok(Self::Left),
+			'D' => Ok(Self::Down),
+			'U' => Ok(Self::Up),
+			_ => Err(()),
+		}
 	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Movement {
+	direction: Direction,
+	count: usize,
+}
+
+impl TryFrom<&str> for Movement {
+	type Error = ();
+
+	fn try_from(line: &str) -> Result<Self, Self::Error> {
+		let (d, c) = line.split_once(' ').ok_or(())?;
+
+		let mut chars = d.chars();
+		let direction = chars.next().ok_or(()).and_then(Direction::try_from)?;
+		if chars.next().is_some() {
+			return Err(());
+		}
 
-	struct Movement {
-		direction: Direction,
-		count: isize,
+		let count = c.parse::<usize>().map_err(|_| ())?;
+
+		Ok(Movement { direction, count })
+	}
+}
+
+type Position = (isize, isize);
+
+#[derive(Debug)]
+pub(crate) struct Rope {
+	// the first knot is the head
+	knots: Vec<Position>,
+	visited: Vec<Option<HashSet<Position>>>,
+	frames: Option<Vec<Vec<Position>>>,
+}
+
+impl Rope {
+	pub fn new(knot_count: usize) -> Self {
+		assert!(knot_count > 0, "a rope needs at least one knot");
+
+		Self {
+			knots: vec![(0, 0); knot_count],
+			visited: vec![None; knot_count],
+			frames: None,
+		}
 	}
 
-	impl TryFrom<char> for Direction {
-		type Error = ();
+	pub fn track(mut self, knot: usize) -> Self {
+		self.visited[knot] = Some(HashSet::from([self.knots[knot]]));
+		self
+	}
+
+	pub fn record(mut self) -> Self {
+		self.frames = Some(vec![self.knots.clone()]);
+		self
+	}
+
+	pub fn knots(&self) -> &[Position] {
+		&self.knots
+	}
+
+	pub fn visited(&self, knot: usize) -> Option<&HashSet<Position>> {
+		self.visited.get(knot)?.as_ref()
+	}
+
+	pub fn frames(&self) -> Option<&[Vec<Position>]> {
+		self.frames.as_deref()
+	}
+
+	pub fn step(&mut self, direction: Direction) {
+		match direction {
+			Direction::Down => self.knots[0].1 += 1,
+			Direction::Left => self.knots[0].0 -= 1,
+			Direction::Right => self.knots[0].0 += 1,
+			Direction::Up => self.knots[0].1 -= 1,
+		}
+
+		for i in 1..self.knots.len() {
+			let previous = self.knots[i - 1];
+			let current = &mut self.knots[i];
+
+			let diff_x = isize::abs_diff(previous.0, current.0);
+			let diff_y = isize::abs_diff(previous.1, current.1);
+
+			if diff_x > 1 || diff_y > 1 {
+				current.0 += (previous.0 - current.0).clamp(-1, 1);
+				current.1 += (previous.1 - current.1).clamp(-1, 1);
+			}
+		}
 
-		fn try_from(c: char) -> Result<Self, Self::Error> {
-			match c {
-				'R' => Ok(Self::Right),
-				'L' => Ok(Self::Left),
-				'D' => Ok(Self::Down),
-				'U' => Ok(Self::Up),
-				_ => Err(()),
+		for (knot, visited) in self.knots.iter().zip(self.visited.iter_mut()) {
+			if let Some(visited) = visited {
+				visited.insert(*knot);
 			}
 		}
+
+		if let Some(frames) = &mut self.frames {
+			frames.push(self.knots.clone());
+		}
 	}
 
-	let instructions = input.split('\n')
-		.map(|line| {
-			let (d, c) = line.split_once(' ').unwrap();
-			let direction = Direction::try_from(d.chars().next().unwrap()).unwrap();
-			let count = c.parse::<isize>().unwrap();
+	pub fn apply(&mut self, movement: &Movement) {
+		for _ in 0..movement.count {
+			self.step(movement.direction);
+		}
+	}
+
+	// one line per frame, listing every knot's position from the head down
+	pub fn dump_frames<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+		for frame in self.frames().unwrap_or_default() {
+			let line = frame.iter()
+				.map(|(x, y)| format!("{},{}", x, y))
+				.collect::<Vec<_>>()
+				.join(" ");
 
-			Movement { direction, count }
-		})
-		.collect::<Vec<_>>();
+			writeln!(writer, "{}", line)?;
+		}
+
+		Ok(())
+	}
+}
+
+pub(crate) fn solve(data: &[u8], part: Part, frames_path: Option<&Path>) {
+	let input = std::str::from_utf8(data).expect("input parse error");
+
+	let instructions = input.split('\n')
+		.map(Movement::try_from)
+		.collect::<Result<Vec<_>, _>>()
+		.expect("input parse error");
 
 	let rope_length = match part {
 		Part::A => 2,
 		Part::B => 10,
 	};
 
-	let mut visited_positions: HashSet<(isize, isize)> = HashSet::new();
-	let mut positions = vec![(0, 0); rope_length];
-	
-	visited_positions.insert(*positions.last().unwrap());
-
-	for instruction in instructions {
-		for _ in 0..instruction.count {
-			match instruction.direction {
-				Direction::Down => positions[0].1 += 1,
-				Direction::Left => positions[0].0 -= 1,
-				Direction::Right => positions[0].0 += 1,
-				Direction::Up => positions[0].1 -= 1,
-			}
+	let mut rope = Rope::new(rope_length).track(rope_length - 1);
+	if frames_path.is_some() {
+		rope = rope.record();
+	}
 
-			for i in 1..positions.len() {
-				let previous = positions[i - 1];
-				let current = &mut positions[i];
-				
-				let diff_x = isize::abs_diff(previous.0, current.0);
-				let diff_y = isize::abs_diff(previous.1, current.1);
-
-				if diff_x > 1 || diff_y > 1 {
-					current.0 += (previous.0 - current.0).clamp(-1, 1);
-					current.1 += (previous.1 - current.1).clamp(-1, 1);
-				}
-			}
+	for instruction in instructions.iter() {
+		rope.apply(instruction);
+	}
+
+	if let Some(path) = frames_path {
+		let file = std::io::BufWriter::new(std::fs::File::create(path).expect("invalid frames path"));
+		rope.dump_frames(file).expect("frame dump error");
+	}
+
+	println!("{}", rope.visited(rope_length - 1).unwrap().len());
+}
 
-			visited_positions.insert(*positions.last().unwrap());
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const EXAMPLE: &str = include_str!("../inputs/9/example.txt");
+
+	#[test]
+	fn example() {
+		let mut rope = Rope::new(2).track(1).record();
+
+		for line in EXAMPLE.split('\n') {
+			rope.apply(&Movement::try_from(line).unwrap());
 		}
+
+		assert_eq!(rope.knots(), [(2, -2), (1, -2)]);
+		assert_eq!(rope.visited(1).unwrap().len(), 13);
+		assert!(rope.visited(0).is_none());
+		assert_eq!(rope.frames().unwrap().len(), 25);
 	}
 
-	println!("{}", visited_positions.len());
+	#[test]
+	fn dump_frames() {
+		let mut rope = Rope::new(2).record();
+		rope.apply(&Movement::try_from("R 2").unwrap());
+
+		let mut output = vec![];
+		rope.dump_frames(&mut output).unwrap();
+
+		assert_eq!(String::from_utf8(output).unwrap(), "0,0 0,0\n1,0 0,0\n2,0 1,0\n");
+	}
 }
\ No newline at end of file
@@ -0,0 +1,84 @@
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone)]
+pub(crate) struct DistanceMatrix {
+	size: usize,
+	distances: Vec<u8>,
+}
+
+impl DistanceMatrix {
+	const UNREACHABLE: u8 = u8::MAX;
+
+	// `edges[n]` lists the nodes which can be reached in one step from `n`,
+	// and `None` is returned if any shortest path is too long to store
+	pub fn from_edges(edges: &[Vec<usize>]) -> Option<Self> {
+		let size = edges.len();
+		let mut distances = vec![Self::UNREACHABLE; size * size];
+		let mut queue = VecDeque::new();
+
+		for (source, row) in distances.chunks_exact_mut(size.max(1)).enumerate() {
+			row[source] = 0;
+			queue.push_back(source);
+
+			while let Some(node) = queue.pop_front() {
+				for neighbour in edges[node].iter().copied() {
+					if row[neighbour] == Self::UNREACHABLE {
+						row[neighbour] = row[node].checked_add(1)
+							.filter(|d| *d != Self::UNREACHABLE)?;
+						queue.push_back(neighbour);
+					}
+				}
+			}
+		}
+
+		Some(Self { size, distances })
+	}
+
+	pub fn get(&self, from: usize, to: usize) -> Option<u8> {
+		let distance = self.distances[from * self.size + to];
+		(distance != Self::UNREACHABLE).then_some(distance)
+	}
+
+	// id `i` in the new matrix refers to `nodes[i]` in this one
+	pub fn select(&self, nodes: &[usize]) -> Self {
+		let distances = nodes.iter()
+			.flat_map(|from| {
+				nodes.iter().map(move |to| self.distances[from * self.size + to])
+			})
+			.collect();
+
+		Self { size: nodes.len(), distances }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// a line of `n` nodes, each joined to the next in both directions
+	fn line(n: usize) -> Vec<Vec<usize>> {
+		(0..n)
+			.map(|i| [i.checked_sub(1), Some(i + 1).filter(|j| *j < n)].into_iter().flatten().collect())
+			.collect()
+	}
+
+	#[test]
+	fn distances() {
+		let mut edges = line(4);
+		edges.push(vec![]);
+
+		let matrix = DistanceMatrix::from_edges(&edges).unwrap();
+		assert_eq!(matrix.get(0, 3), Some(3));
+		assert_eq!(matrix.get(2, 1), Some(1));
+		assert_eq!(matrix.get(0, 4), None);
+
+		let selected = matrix.select(&[3, 0]);
+		assert_eq!(selected.get(0, 1), Some(3));
+	}
+
+	#[test]
+	fn longest_paths() {
+		assert_eq!(DistanceMatrix::from_edges(&line(255)).unwrap().get(0, 254), Some(254));
+		assert!(DistanceMatrix::from_edges(&line(256)).is_none());
+	}
+}
\ No newline at end of file
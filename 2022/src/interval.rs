@@ -0,0 +1,191 @@
+use std::ops::{Add, Sub};
+use std::str::FromStr;
+
+// std only has these as inherent methods on each integer type
+pub(crate) trait CheckedArithmetic: Sized {
+	fn checked_add(self, other: Self) -> Option<Self>;
+	fn checked_sub(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_checked_arithmetic {
+	($($t:ty),*) => {
+		$(impl CheckedArithmetic for $t {
+			fn checked_add(self, other: Self) -> Option<Self> {
+				<$t>::checked_add(self, other)
+			}
+
+			fn checked_sub(self, other: Self) -> Option<Self> {
+				<$t>::checked_sub(self, other)
+			}
+		})*
+	};
+}
+
+impl_checked_arithmetic!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+// inclusive at both ends, and never empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Interval<T> {
+	start: T,
+	end: T,
+}
+
+// whether an interval ending at `end` overlaps or is directly followed by one starting at `start`,
+// stepping back from `start` rather than forward from `end` so it can't overflow
+fn touches<T>(end: T, start: T) -> bool
+where T: Copy + Ord + Sub<Output = T> + From<u8> {
+	start <= end || start - T::from(1) <= end
+}
+
+impl<T> Interval<T>
+where T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8> {
+	pub fn new(start: T, end: T) -> Option<Self> {
+		(start <= end).then_some(Self { start, end })
+	}
+
+	pub fn contains(&self, value: T) -> bool {
+		self.start <= value && value <= self.end
+	}
+
+	// `None` if the length doesn't fit in `T`
+	pub fn len(&self) -> Option<T>
+	where T: CheckedArithmetic {
+		self.end.checked_sub(self.start)?.checked_add(T::from(1))
+	}
+
+	pub fn contains_interval(&self, other: &Self) -> bool {
+		self.start <= other.start && other.end <= self.end
+	}
+
+	pub fn overlaps(&self, other: &Self) -> bool {
+		self.start <= other.end && other.start <= self.end
+	}
+
+	pub fn intersection(&self, other: &Self) -> Option<Self> {
+		Self::new(self.start.max(other.start), self.end.min(other.end))
+	}
+
+	// `None` if there would be a gap between them
+	pub fn union(&self, other: &Self) -> Option<Self> {
+		let (first, second) = if self.start <= other.start { (self, other) } else { (other, self) };
+
+		touches(first.end, second.start)
+			.then(|| Self { start: first.start, end: first.end.max(second.end) })
+	}
+}
+
+#[derive(Debug)]
+pub(crate) enum IntervalParseError {
+	MissingSeparator(String),
+	BadBound(String),
+	Reversed(String),
+}
+
+impl<T> FromStr for Interval<T>
+where T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8> + FromStr {
+	type Err = IntervalParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (start, end) = s.split_once('-')
+			.ok_or_else(|| IntervalParseError::MissingSeparator(s.to_owned()))?;
+
+		let bound = |b: &str| b.parse::<T>().map_err(|_| IntervalParseError::BadBound(b.to_owned()));
+
+		Self::new(bound(start)?, bound(end)?)
+			.ok_or_else(|| IntervalParseError::Reversed(s.to_owned()))
+	}
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct IntervalSet<T> {
+	// sorted, with no two overlapping or adjacent
+	intervals: Vec<Interval<T>>,
+}
+
+impl<T> FromIterator<Interval<T>> for IntervalSet<T>
+where T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8> {
+	fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+		let mut sorted = iter.into_iter().collect::<Vec<_>>();
+		sorted.sort_unstable_by_key(|i| i.start);
+
+		let mut intervals = Vec::<Interval<T>>::with_capacity(sorted.len());
+
+		for interval in sorted {
+			match intervals.last_mut() {
+				Some(last) => match last.union(&interval) {
+					Some(merged) => *last = merged,
+					None => intervals.push(interval),
+				},
+				None => intervals.push(interval),
+			}
+		}
+
+		Self { intervals }
+	}
+}
+
+impl<T> IntervalSet<T>
+where T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8> {
+	pub fn contains(&self, value: T) -> bool {
+		let index = self.intervals.partition_point(|i| i.end < value);
+		self.intervals.get(index).is_some_and(|i| i.contains(value))
+	}
+
+	// `None` if the length doesn't fit in `T`
+	pub fn len(&self) -> Option<T>
+	where T: CheckedArithmetic {
+		self.intervals.iter()
+			.try_fold(T::from(0), |total, interval| total.checked_add(interval.len()?))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn interval(start: u64, end: u64) -> Interval<u64> {
+		Interval::new(start, end).unwrap()
+	}
+
+	#[test]
+	fn intersection() {
+		assert_eq!(interval(2, 6).intersection(&interval(4, 8)), Some(interval(4, 6)));
+		assert_eq!(interval(2, 6).intersection(&interval(3, 4)), Some(interval(3, 4)));
+		assert_eq!(interval(2, 6).intersection(&interval(6, 9)), Some(interval(6, 6)));
+		assert_eq!(interval(2, 6).intersection(&interval(7, 9)), None);
+	}
+
+	#[test]
+	fn union() {
+		assert_eq!(interval(2, 6).union(&interval(4, 8)), Some(interval(2, 8)));
+		assert_eq!(interval(7, 9).union(&interval(2, 6)), Some(interval(2, 9)));
+		assert_eq!(interval(2, 6).union(&interval(8, 9)), None);
+		assert_eq!(interval(5, u64::MAX).union(&interval(u64::MAX, u64::MAX)), Some(interval(5, u64::MAX)));
+		assert_eq!(interval(0, 0).union(&interval(u64::MAX, u64::MAX)), None);
+	}
+
+	#[test]
+	fn set_merges_at_the_maximum() {
+		let set = [interval(u64::MAX - 1, u64::MAX), interval(0, 3), interval(u64::MAX, u64::MAX), interval(4, 4)]
+			.into_iter()
+			.collect::<IntervalSet<_>>();
+
+		assert_eq!(set.intervals, [interval(0, 4), interval(u64::MAX - 1, u64::MAX)]);
+		assert!(set.contains(u64::MAX));
+		assert!(!set.contains(5));
+		assert_eq!(set.len(), Some(7));
+
+		let full = [interval(0, u64::MAX)].into_iter().collect::<IntervalSet<_>>();
+		assert_eq!(interval(0, u64::MAX).len(), None);
+		assert_eq!(interval(1, u64::MAX).len(), Some(u64::MAX));
+		assert_eq!(full.len(), None);
+	}
+
+	#[test]
+	fn parse() {
+		assert_eq!("2-4".parse::<Interval<u64>>().ok(), Some(interval(2, 4)));
+		assert!(matches!("5-3".parse::<Interval<u64>>(), Err(IntervalParseError::Reversed(_))));
+		assert!(matches!("x-3".parse::<Interval<u64>>(), Err(IntervalParseError::BadBound(_))));
+		assert!(matches!("3".parse::<Interval<u64>>(), Err(IntervalParseError::MissingSeparator(_))));
+	}
+}
\ No newline at end of file
@@ -1,8 +1,7 @@
 use std::{path::PathBuf, str::Utf8Error, ops::RangeInclusive, collections::{HashSet, HashMap, hash_map::Entry}, cmp::Ordering};
 
-use clap::{Parser, builder::PossibleValue};
+use clap::{Parser, CommandFactory, builder::PossibleValue, error::ErrorKind};
 use nom::{bytes::complete::tag, sequence::{preceded, tuple, delimited}, combinator::{map_res, map}, character::complete::{digit1, alpha1}, multi::separated_list0, Parser as NomParser};
-use petgraph::prelude::NodeIndex;
 
 mod day_1;
 mod day_2;
@@ -24,6 +23,11 @@ mod day_17;
 mod day_18;
 mod day_19;
 
+mod distance_matrix;
+mod interval;
+mod packet;
+mod tree;
+
 pub(crate) trait Input: Sized {
 	type Error;
 
@@ -122,6 +126,24 @@ struct Args {
 	#[arg(value_enum)]
 	part: Part,
 	input_path: PathBuf,
+	/// How many moves the day 2 game has, which must be odd
+	#[arg(long)]
+	moves: Option<usize>,
+	/// Find the reading of the day 2 guide's second column which scores highest
+	#[arg(long)]
+	best_mapping: bool,
+	/// Where to write the day 9 rope's position after every step
+	#[arg(long)]
+	frames: Option<PathBuf>,
+	/// Draw the day 12 route over the height map on stderr
+	#[arg(long)]
+	route: bool,
+	/// Where to write a mesh of the day 18 droplet, as .obj or .stl
+	#[arg(long)]
+	mesh: Option<PathBuf>,
+	/// Leave the faces around trapped air pockets out of the mesh
+	#[arg(long, requires = "mesh")]
+	mesh_exterior: bool,
 }
 
 pub fn take_positive_number<Num>(input: &str) -> nom::IResult<&str, Num>
@@ -143,6 +165,21 @@ where Num: std::str::FromStr {
 fn main() {
 	let args = Args::parse();
 
+	// flags which only apply to a single day
+	let misplaced = [
+		("--moves", args.moves.is_some() && !matches!(args.day, Day::Two)),
+		("--best-mapping", args.best_mapping && !matches!((&args.day, &args.part), (Day::Two, Part::A))),
+		("--frames", args.frames.is_some() && !matches!(args.day, Day::Nine)),
+		("--route", args.route && !matches!(args.day, Day::Twelve)),
+		("--mesh", args.mesh.is_some() && !matches!(args.day, Day::Eighteen)),
+	];
+
+	if let Some((flag, _)) = misplaced.iter().find(|(_, m)| *m) {
+		Args::command()
+			.error(ErrorKind::ArgumentConflict, format!("{} doesn't apply to this day", flag))
+			.exit();
+	}
+
 	let data = std::fs::read(&args.input_path).expect("invalid path");
 
 	let is_example = args.input_path.file_name().unwrap()
@@ -151,20 +188,28 @@ fn main() {
 
 	match (args.day, &args.part) {
 		(Day::One, Part::A) => {
-			let input = day_1::CalorieList::parse(&data).expect("input parse error");
-			println!("{}", input.top());
+			let top = day_1::top_n(data.as_slice(), 1).expect("input parse error");
+			println!("{}", top[0]);
 		},
 		(Day::One, Part::B) => {
-			let input = day_1::CalorieList::parse(&data).expect("input parse error");
-			println!("{:?}", input.top_n(3));
+			let top = day_1::top_n(data.as_slice(), 3).expect("input parse error");
+			println!("{}", top.iter().sum::<usize>());
 		},
 		(Day::Two, Part::A) => {
-			let input = day_2::ActionStrategyGuide::parse(&data).expect("input parse error");
-			println!("{}", input.score());
+			let game = args.moves.map_or(Some(day_2::ROCK_PAPER_SCISSORS), day_2::Game::new).expect("the day 2 game needs an odd number of moves");
+			let input = day_2::StrategyGuide::parse(&data).expect("input parse error");
+
+			if args.best_mapping {
+				let (mapping, score) = input.best_mapping(game).expect("solve error");
+				println!("{:?} scores {}", mapping, score);
+			} else {
+				println!("{}", input.score(game, &day_2::Column::in_order(game)).expect("solve error"));
+			}
 		},
 		(Day::Two, Part::B) => {
-			let input = day_2::OutcomeStrategyGuide::parse(&data).expect("input parse error");
-			println!("{}", input.score());
+			let game = args.moves.map_or(Some(day_2::ROCK_PAPER_SCISSORS), day_2::Game::new).expect("the day 2 game needs an odd number of moves");
+			let input = day_2::StrategyGuide::parse(&data).expect("input parse error");
+			println!("{}", input.score(game, &day_2::Column::Outcome).expect("solve error"));
 		},
 		(Day::Three, Part::A) => {
 			let input = day_3::Packing::parse(&data).expect("input parse error");
@@ -172,7 +217,7 @@ fn main() {
 		},
 		(Day::Three, Part::B) => {
 			let input = day_3::Packing::parse(&data).expect("input parse error");
-			println!("{}", input.badges_priority_sum().expect("solve error"));
+			println!("{}", input.badges_priority_sum(3).expect("solve error"));
 		},
 		(Day::Four, _) => {
 			day_4::solve(&data, args.part);
@@ -199,7 +244,7 @@ fn main() {
 			day_8::solve(&data, args.part);
 		},
 		(Day::Nine, _) => {
-			day_9::solve(&data, args.part);
+			day_9::solve(&data, args.part, args.frames.as_deref());
 		},
 		(Day::Ten, _) => {
 			day_10::solve(&data, args.part);
@@ -208,7 +253,7 @@ fn main() {
 			day_11::solve(&data, args.part);
 		},
 		(Day::Twelve, _) => {
-			day_12::solve(&data, args.part);
+			day_12::solve(&data, args.part, args.route);
 		},
 		(Day::Thirteen, _) => {
 			day_13::solve(&data, args.part);
@@ -226,7 +271,7 @@ fn main() {
 			day_17::solve(&data, args.part);
 		},
 		(Day::Eighteen, _) => {
-			day_18::solve(&data, args.part);
+			day_18::solve(&data, args.part, args.mesh.as_deref(), args.mesh_exterior);
 		},
 		(Day::Nineteen, _) => {
 			unimplemented!("Day 19 unsolved");
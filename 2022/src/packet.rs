@@ -0,0 +1,102 @@
+use std::cmp::Ordering;
+
+use nom::{branch::alt, sequence::delimited, combinator::map, bytes::complete::tag, multi::separated_list0, character::complete::digit1};
+
+// kept as the digits it was written with, so it can be any size
+#[derive(Debug, Clone)]
+pub(crate) struct Integer(String);
+
+impl Integer {
+	fn significant_digits(&self) -> &str {
+		let digits = self.0.trim_start_matches('0');
+		if digits.is_empty() { "0" } else { digits }
+	}
+}
+
+impl PartialEq for Integer {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl Eq for Integer {}
+
+impl PartialOrd for Integer {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Integer {
+	fn cmp(&self, other: &Self) -> Ordering {
+		let a = self.significant_digits();
+		let b = other.significant_digits();
+
+		// with no leading zeros, more digits means a larger number
+		a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+	}
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Packet {
+	Int(Integer),
+	List(Vec<Packet>),
+}
+
+impl Packet {
+	pub fn parse(input: &str) -> nom::IResult<&str, Self> {
+		alt((
+			map(digit1, |d: &str| Self::Int(Integer(d.to_owned()))),
+			map(delimited(tag("["), separated_list0(tag(","), Packet::parse), tag("]")), Self::List),
+		))(input)
+	}
+
+	// counting from 1, as if sorted in among `packets`
+	pub fn rank(&self, packets: &[Packet]) -> usize {
+		packets.iter().filter(|p| *p < self).count() + 1
+	}
+}
+
+impl std::fmt::Display for Packet {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Packet::Int(Integer(digits)) => write!(f, "{}", digits),
+			Packet::List(items) => {
+				write!(f, "[")?;
+				for (i, item) in items.iter().enumerate() {
+					if i > 0 {
+						write!(f, ",")?;
+					}
+					write!(f, "{}", item)?;
+				}
+				write!(f, "]")
+			},
+		}
+	}
+}
+
+impl PartialEq for Packet {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl Eq for Packet {}
+
+impl PartialOrd for Packet {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Packet {
+	fn cmp(&self, other: &Self) -> Ordering {
+		// an integer compared against a list acts as a list of just itself
+		match (self, other) {
+			(Packet::Int(a), Packet::Int(b)) => a.cmp(b),
+			(Packet::Int(_), Packet::List(b)) => std::slice::from_ref(self).cmp(b.as_slice()),
+			(Packet::List(a), Packet::Int(_)) => a.as_slice().cmp(std::slice::from_ref(other)),
+			(Packet::List(a), Packet::List(b)) => a.cmp(b),
+		}
+	}
+}
\ No newline at end of file
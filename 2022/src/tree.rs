@@ -0,0 +1,258 @@
+use std::collections::VecDeque;
+
+#[derive(Debug)]
+struct Node<T> {
+	data: T,
+	children: Vec<usize>,
+	parent: Option<usize>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Tree<T> {
+	// children are always added after their parent, so have higher ids
+	nodes: Vec<Node<T>>
+}
+
+impl<T> Default for Tree<T> {
+	fn default() -> Self {
+		Self { nodes: vec![] }
+	}
+}
+
+impl<T> Tree<T> {
+	pub fn push_root(&mut self, data: T) -> usize {
+		self.nodes.push(Node {
+			data,
+			parent: None,
+			children: vec![],
+		});
+		self.nodes.len() - 1
+	}
+
+	pub fn push_child(&mut self, data: T, parent: usize) -> usize {
+		self.nodes.push(Node {
+			data,
+			parent: Some(parent),
+			children: vec![],
+		});
+		let id = self.nodes.len() - 1;
+
+		self.nodes[parent].children.push(id);
+
+		id
+	}
+
+	pub fn get(&self, node_id: usize) -> Option<&T> {
+		self.nodes.get(node_id).map(|n| &n.data)
+	}
+
+	pub fn get_mut(&mut self, node_id: usize) -> Option<&mut T> {
+		self.nodes.get_mut(node_id).map(|n| &mut n.data)
+	}
+
+	pub fn children(&self, node_id: usize) -> Option<&[usize]> {
+		self.nodes.get(node_id).map(|n| n.children.as_slice())
+	}
+
+	pub fn parent(&self, node_id: usize) -> Option<usize> {
+		self.nodes.get(node_id).and_then(|n| n.parent)
+	}
+
+	pub fn len(&self) -> usize {
+		self.nodes.len()
+	}
+
+	// like the other lookups, the traversals give `None` for a node that doesn't exist
+	pub fn pre_order(&self, start: usize) -> Option<PreOrder<'_, T>> {
+		self.nodes.get(start)?;
+		Some(PreOrder { tree: self, stack: vec![start] })
+	}
+
+	pub fn post_order(&self, start: usize) -> Option<PostOrder<'_, T>> {
+		self.nodes.get(start)?;
+		Some(PostOrder { tree: self, stack: vec![(start, false)] })
+	}
+
+	pub fn breadth_first(&self, start: usize) -> Option<BreadthFirst<'_, T>> {
+		self.nodes.get(start)?;
+		Some(BreadthFirst { tree: self, queue: VecDeque::from([start]) })
+	}
+
+	pub fn ancestors(&self, node_id: usize) -> Option<Ancestors<'_, T>> {
+		self.nodes.get(node_id)?;
+		Some(Ancestors { tree: self, current: self.parent(node_id) })
+	}
+
+	pub fn path(&self, node_id: usize) -> Option<Vec<usize>> {
+		let mut path = std::iter::once(node_id)
+			.chain(self.ancestors(node_id)?)
+			.collect::<Vec<_>>();
+		path.reverse();
+		Some(path)
+	}
+
+	// the results are indexed by node id, with `None` outside the subtree
+	pub fn fold<B, F>(&self, start: usize, mut f: F) -> Option<Vec<Option<B>>>
+	where F: FnMut(&T, Vec<&B>) -> B {
+		let mut results = std::iter::repeat_with(|| None)
+			.take(self.nodes.len())
+			.collect::<Vec<_>>();
+
+		for node_id in self.post_order(start)? {
+			let node = &self.nodes[node_id];
+			let children = node.children.iter()
+				.map(|c| results[*c].as_ref().unwrap())
+				.collect();
+
+			let result = f(&node.data, children);
+			results[node_id] = Some(result);
+		}
+
+		Some(results)
+	}
+}
+
+pub(crate) struct PreOrder<'a, T> {
+	tree: &'a Tree<T>,
+	stack: Vec<usize>,
+}
+
+impl<T> Iterator for PreOrder<'_, T> {
+	type Item = usize;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let node_id = self.stack.pop()?;
+		self.stack.extend(self.tree.nodes[node_id].children.iter().rev());
+		Some(node_id)
+	}
+}
+
+pub(crate) struct PostOrder<'a, T> {
+	tree: &'a Tree<T>,
+	// whether each node's children have been queued yet
+	stack: Vec<(usize, bool)>,
+}
+
+impl<T> Iterator for PostOrder<'_, T> {
+	type Item = usize;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let (node_id, expanded) = self.stack.pop()?;
+
+			if expanded {
+				return Some(node_id);
+			}
+
+			self.stack.push((node_id, true));
+			self.stack.extend(self.tree.nodes[node_id].children.iter().rev().map(|c| (*c, false)));
+		}
+	}
+}
+
+pub(crate) struct BreadthFirst<'a, T> {
+	tree: &'a Tree<T>,
+	queue: VecDeque<usize>,
+}
+
+impl<T> Iterator for BreadthFirst<'_, T> {
+	type Item = usize;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let node_id = self.queue.pop_front()?;
+		self.queue.extend(self.tree.nodes[node_id].children.iter());
+		Some(node_id)
+	}
+}
+
+pub(crate) struct Ancestors<'a, T> {
+	tree: &'a Tree<T>,
+	current: Option<usize>,
+}
+
+impl<T> Iterator for Ancestors<'_, T> {
+	type Item = usize;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let node_id = self.current?;
+		self.current = self.tree.parent(node_id);
+		Some(node_id)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	//     0
+	//   /   \
+	//  1     2
+	// / \    |
+	// 3  4   5
+	fn example() -> Tree<usize> {
+		let mut tree = Tree::default();
+		let root = tree.push_root(0);
+		let a = tree.push_child(1, root);
+		let b = tree.push_child(2, root);
+		tree.push_child(3, a);
+		tree.push_child(4, a);
+		tree.push_child(5, b);
+		tree
+	}
+
+	#[test]
+	fn structure() {
+		let tree = example();
+
+		assert_eq!(tree.len(), 6);
+		assert_eq!(tree.children(1), Some([3, 4].as_slice()));
+		assert_eq!(tree.parent(5), Some(2));
+		assert_eq!(tree.parent(0), None);
+	}
+
+	#[test]
+	fn traversals() {
+		let tree = example();
+
+		assert_eq!(tree.pre_order(0).unwrap().collect::<Vec<_>>(), [0, 1, 3, 4, 2, 5]);
+		assert_eq!(tree.post_order(0).unwrap().collect::<Vec<_>>(), [3, 4, 1, 5, 2, 0]);
+		assert_eq!(tree.breadth_first(0).unwrap().collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5]);
+		assert_eq!(tree.pre_order(2).unwrap().collect::<Vec<_>>(), [2, 5]);
+	}
+
+	#[test]
+	fn ancestors() {
+		let tree = example();
+
+		assert_eq!(tree.ancestors(4).unwrap().collect::<Vec<_>>(), [1, 0]);
+		assert_eq!(tree.ancestors(0).unwrap().count(), 0);
+		assert_eq!(tree.path(5), Some(vec![0, 2, 5]));
+		assert_eq!(tree.path(0), Some(vec![0]));
+	}
+
+	#[test]
+	fn fold() {
+		let tree = example();
+
+		let mut order = vec![];
+		let sums = tree.fold(1, |value, children| {
+			order.push(*value);
+			value + children.into_iter().sum::<usize>()
+		});
+
+		assert_eq!(order, [3, 4, 1]);
+		assert_eq!(sums, Some(vec![None, Some(8), None, Some(3), Some(4), None]));
+	}
+
+	#[test]
+	fn unknown_nodes() {
+		let tree = example();
+
+		assert!(tree.pre_order(6).is_none());
+		assert!(tree.post_order(6).is_none());
+		assert!(tree.breadth_first(6).is_none());
+		assert!(tree.ancestors(6).is_none());
+		assert!(tree.path(6).is_none());
+		assert!(tree.fold(6, |_, _: Vec<&()>| ()).is_none());
+	}
+}
\ No newline at end of file